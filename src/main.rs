@@ -1,10 +1,11 @@
-use std::str::FromStr;
+use std::{cell::RefCell, collections::HashSet, str::FromStr};
 
 use anyhow::Result;
 use clap::{Parser, Subcommand, ValueEnum};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
-use crate::subcommands::{Generate, Print};
+use crate::subcommands::{Docs, ExportSchema, Generate, Lint, Print};
 
 mod spec;
 mod subcommands;
@@ -22,10 +23,16 @@ struct Cli {
 
 #[derive(Debug, Subcommand)]
 enum Subcommands {
-    #[clap(about = "Generate Rust code")]
+    #[clap(about = "Generate Rust code, TypeScript declarations or protobuf definitions")]
     Generate(Generate),
     #[clap(about = "Print the spec to standard output")]
     Print(Print),
+    #[clap(about = "Export JSON Schema bundles for method params and results")]
+    ExportSchema(ExportSchema),
+    #[clap(about = "Generate an API reference for the spec")]
+    Docs(Docs),
+    #[clap(about = "Check the spec against lint rules")]
+    Lint(Lint),
 }
 
 #[derive(Debug, Clone)]
@@ -47,6 +54,7 @@ enum SpecVersion {
 struct RawSpecs {
     main: &'static str,
     write: &'static str,
+    trace: &'static str,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,17 +63,164 @@ struct ProfileOptions {
     flatten_options: FlattenOption,
     ignore_types: Vec<String>,
     fixed_field_types: FixedFieldsOptions,
-    arc_wrapped_types: ArcWrappingOptions,
+    field_wrapping: FieldWrappingOptions,
+    #[serde(default)]
+    hoisting: HoistingOptions,
+    #[serde(default)]
+    pattern_newtypes: Vec<String>,
+    #[serde(default)]
+    non_zero_integers: bool,
+    #[serde(default)]
+    deprecations: DeprecationOptions,
+    #[serde(default)]
+    builders: Vec<String>,
+    /// Structs getting a borrowed `Ref<'a>` version, along with tests checking that both serialize
+    /// the same way.
+    #[serde(default)]
+    ref_types: Vec<String>,
+    #[serde(default)]
+    extra_attributes: AttributeOptions,
+    #[serde(default)]
+    query_version: QueryVersionOptions,
+    #[serde(default)]
+    conversions: Vec<TypeConversion>,
+    /// Flattened schemas to generate accessor traits for.
+    #[serde(default)]
+    fragment_traits: Vec<String>,
+    #[serde(default)]
+    naming: NamingOptions,
+    /// Enums generated from string `enum`s that get an `Unknown(String)` variant for values missing
+    /// from the specification, and are marked `#[non_exhaustive]`. As the variant owns a `String`,
+    /// these enums no longer derive `Copy`, which is a breaking change for code copying them.
+    #[serde(default)]
+    unknown_variants: Vec<String>,
+    /// Values accepted by `bool` variants of untagged enums, for schemas that only allow one value
+    /// without saying so with a `const` or `enum`. Keys are Rust type names and variant names
+    /// joined by `.`.
+    #[serde(default)]
+    bool_variants: IndexMap<String, bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct FixedFieldsOptions {
+    /// Treats properties with a single-value string `enum` or a `const` as fixed fields. Entries
+    /// in `fixed_field_types` take precedence over inferred ones.
+    #[serde(default)]
+    infer_from_spec: bool,
     fixed_field_types: Vec<RustTypeWithFixedFields>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct ArcWrappingOptions {
-    arc_wrapped_types: Vec<RustTypeWithArcWrappedFields>,
+struct FieldWrappingOptions {
+    wrapped_types: Vec<RustTypeWithWrappedFields>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HoistingOptions {
+    /// Maps synthesized type names (parent type name + property name) to the names to be used.
+    renames: IndexMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NamingOptions {
+    /// Conversion of schema names into type names, and of enum values and error names into variant
+    /// names, for items without explicit renames.
+    #[serde(default)]
+    case: CaseStrategy,
+    /// Maps schema names to type names.
+    #[serde(default)]
+    types: IndexMap<String, String>,
+    /// Maps property and param names to field names. Keys are either property names, or Rust type
+    /// names and property names joined by `.`.
+    #[serde(default)]
+    fields: IndexMap<String, String>,
+    /// Maps enum values and error names to variant names. Keys are either values, or Rust type
+    /// names and values joined by `.`.
+    #[serde(default)]
+    variants: IndexMap<String, String>,
+    /// Keys looked up in the maps above, as unused keys are most likely typos.
+    #[serde(skip)]
+    used_keys: RefCell<HashSet<(&'static str, String)>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum CaseStrategy {
+    /// `PascalCase` with the conventions of starknet-rs: `Txn` spelled out as `Transaction`, and
+    /// renames of some types (mostly legacy contract classes).
+    #[default]
+    StarknetRs,
+    /// Plain `PascalCase`.
+    PascalCase,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeprecationOptions {
+    /// Treats items whose descriptions say they're deprecated as if they were marked `deprecated`.
+    #[serde(default)]
+    infer_from_descriptions: bool,
+    /// Items deprecated regardless of the specification. Names are Rust type names, Rust type
+    /// names and field names joined by `.`, or method names.
+    #[serde(default)]
+    items: Vec<DeprecatedItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct QueryVersionOptions {
+    /// Added to transaction versions to get their query-only counterparts, as a hex string.
+    offset: String,
+    /// Transaction versions, as hex strings, that can be used by query-version fixed fields. Any
+    /// version is accepted when empty.
+    #[serde(default)]
+    valid_versions: Vec<String>,
+}
+
+/// Conversion between two generated structs, with fields mapped by name. Target fields missing
+/// from the source are taken as method parameters, or set to `None` when optional.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TypeConversion {
+    from: String,
+    to: String,
+    /// Name of the conversion method. Defaults to a `From` impl when no parameter is needed, and
+    /// to `into_` followed by the snake-cased target type name otherwise.
+    #[serde(default)]
+    method: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeprecatedItem {
+    name: String,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AttributeOptions {
+    /// Derives added to all generated types.
+    #[serde(default)]
+    derives: Vec<String>,
+    /// Raw attributes (e.g. `#[cfg_attr(...)]`) added to all generated types.
+    #[serde(default)]
+    attributes: Vec<String>,
+    /// Derives and attributes added to specific types on top of the global ones.
+    #[serde(default)]
+    types: Vec<RustTypeWithAttributes>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RustTypeWithAttributes {
+    name: String,
+    #[serde(default)]
+    derives: Vec<String>,
+    #[serde(default)]
+    attributes: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,9 +230,21 @@ struct RustTypeWithFixedFields {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct RustTypeWithArcWrappedFields {
+struct RustTypeWithWrappedFields {
     name: String,
-    fields: Vec<String>,
+    fields: IndexMap<String, FieldWrapper>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum FieldWrapper {
+    /// `Arc` where available, `Box` otherwise.
+    OwnedPtr,
+    Arc,
+    Box,
+    Rc,
+    /// `Cow<'a, str>` for borrowed deserialization. Only applicable to `String` and
+    /// `Option<String>` fields.
+    Cow,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,6 +261,19 @@ struct FixedField {
 enum FlattenOption {
     All,
     Selected(Vec<String>),
+    /// Flattens `allOf` fragments that are never used standalone, with explicit exceptions.
+    Auto(FlattenExceptions),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FlattenExceptions {
+    /// Schemas to be flattened even though they're also used standalone.
+    #[serde(default)]
+    flatten: Vec<String>,
+    /// Schemas to be kept as `#[serde(flatten)]` fields even if only used as fragments.
+    #[serde(default)]
+    no_flatten: Vec<String>,
 }
 
 impl FromStr for SpecVersion {
@@ -141,13 +321,124 @@ impl FixedFieldsOptions {
     }
 }
 
-impl ArcWrappingOptions {
-    fn in_field_wrapped(&self, type_name: &str, field_name: &str) -> bool {
-        self.arc_wrapped_types.iter().any(|item| {
+impl Default for QueryVersionOptions {
+    fn default() -> Self {
+        Self {
+            // 2^128
+            offset: String::from("0x100000000000000000000000000000000"),
+            valid_versions: vec![],
+        }
+    }
+}
+
+impl HoistingOptions {
+    fn resolve_name(&self, name: &str) -> String {
+        self.renames
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_owned())
+    }
+}
+
+impl NamingOptions {
+    fn find_type(&self, schema_name: &str) -> Option<&String> {
+        self.find("types", &self.types, schema_name)
+    }
+
+    fn find_field(&self, type_name: &str, property: &str) -> Option<&String> {
+        self.find("fields", &self.fields, &format!("{type_name}.{property}"))
+            .or_else(|| self.find("fields", &self.fields, property))
+    }
+
+    fn find_variant(&self, type_name: &str, value: &str) -> Option<&String> {
+        self.find("variants", &self.variants, &format!("{type_name}.{value}"))
+            .or_else(|| self.find("variants", &self.variants, value))
+    }
+
+    fn find<'a>(
+        &self,
+        option: &'static str,
+        renames: &'a IndexMap<String, String>,
+        key: &str,
+    ) -> Option<&'a String> {
+        let value = renames.get(key);
+        if value.is_some() {
+            self.used_keys.borrow_mut().insert((option, key.to_owned()));
+        }
+        value
+    }
+
+    /// Fails on keys that haven't been looked up.
+    fn check_unused_keys(&self) -> Result<()> {
+        let used_keys = self.used_keys.borrow();
+        for (option, renames) in [
+            ("types", &self.types),
+            ("fields", &self.fields),
+            ("variants", &self.variants),
+        ] {
+            if let Some(key) = renames
+                .keys()
+                .find(|key| !used_keys.contains(&(option, key.to_string())))
+            {
+                anyhow::bail!("Key in naming.{option} matches nothing: {key}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl AttributeOptions {
+    fn find_derives(&self, type_name: &str) -> Vec<String> {
+        self.derives
+            .iter()
+            .chain(
+                self.types
+                    .iter()
+                    .filter(|item| item.name == type_name)
+                    .flat_map(|item| item.derives.iter()),
+            )
+            .cloned()
+            .collect()
+    }
+
+    fn find_attributes(&self, type_name: &str) -> Vec<String> {
+        self.attributes
+            .iter()
+            .chain(
+                self.types
+                    .iter()
+                    .filter(|item| item.name == type_name)
+                    .flat_map(|item| item.attributes.iter()),
+            )
+            .cloned()
+            .collect()
+    }
+}
+
+impl DeprecationOptions {
+    fn find_deprecation(&self, name: &str) -> Option<String> {
+        self.items.iter().find_map(|item| {
+            if item.name == name {
+                Some(
+                    item.note
+                        .clone()
+                        .unwrap_or_else(|| String::from("Deprecated by the specification")),
+                )
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl FieldWrappingOptions {
+    fn find_field_wrapper(&self, type_name: &str, field_name: &str) -> Option<FieldWrapper> {
+        self.wrapped_types.iter().find_map(|item| {
             if item.name == type_name {
-                item.fields.iter().any(|field| field == field_name)
+                item.fields.get(field_name).copied()
             } else {
-                false
+                None
             }
         })
     }
@@ -162,6 +453,7 @@ fn main() {
             raw_specs: RawSpecs {
                 main: include_str!("./specs/0.1.0/starknet_api_openrpc.json"),
                 write: include_str!("./specs/0.1.0/starknet_write_api.json"),
+                trace: include_str!("./specs/0.1.0/starknet_trace_api_openrpc.json"),
             },
             options: serde_json::from_str(include_str!("./profiles/0.1.0.json"))
                 .expect("Unable to parse profile options"),
@@ -171,6 +463,7 @@ fn main() {
             raw_specs: RawSpecs {
                 main: include_str!("./specs/0.2.1/starknet_api_openrpc.json"),
                 write: include_str!("./specs/0.2.1/starknet_write_api.json"),
+                trace: include_str!("./specs/0.2.1/starknet_trace_api_openrpc.json"),
             },
             options: serde_json::from_str(include_str!("./profiles/0.2.1.json"))
                 .expect("Unable to parse profile options"),
@@ -180,6 +473,7 @@ fn main() {
             raw_specs: RawSpecs {
                 main: include_str!("./specs/0.3.0/starknet_api_openrpc.json"),
                 write: include_str!("./specs/0.3.0/starknet_write_api.json"),
+                trace: include_str!("./specs/0.3.0/starknet_trace_api_openrpc.json"),
             },
             options: serde_json::from_str(include_str!("./profiles/0.3.0.json"))
                 .expect("Unable to parse profile options"),
@@ -189,6 +483,7 @@ fn main() {
             raw_specs: RawSpecs {
                 main: include_str!("./specs/0.4.0/starknet_api_openrpc.json"),
                 write: include_str!("./specs/0.4.0/starknet_write_api.json"),
+                trace: include_str!("./specs/0.4.0/starknet_trace_api_openrpc.json"),
             },
             options: serde_json::from_str(include_str!("./profiles/0.4.0.json"))
                 .expect("Unable to parse profile options"),
@@ -198,6 +493,9 @@ fn main() {
     let result = match cli.command {
         Subcommands::Generate(cmd) => cmd.run(&profiles),
         Subcommands::Print(cmd) => cmd.run(&profiles),
+        Subcommands::ExportSchema(cmd) => cmd.run(&profiles),
+        Subcommands::Docs(cmd) => cmd.run(&profiles),
+        Subcommands::Lint(cmd) => cmd.run(&profiles),
     };
 
     result.expect("Error running commmand");
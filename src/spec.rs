@@ -32,6 +32,8 @@ pub struct Method {
     pub result: MethodResult,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub errors: Option<Vec<Reference>>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +58,8 @@ pub struct Param {
     pub summary: Option<String>,
     pub required: bool,
     pub schema: Schema,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,11 +79,16 @@ pub enum Schema {
     Ref(Reference),
     OneOf(OneOf),
     AllOf(AllOf),
+    AnyOf(AnyOf),
+    Not(Not),
     Primitive(Primitive),
 }
 
+// Schema objects can't use `deny_unknown_fields` as `x-*` extensions are collected with
+// `#[serde(flatten)]`. Unknown keywords are instead rejected by `Extensions`.
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
 pub struct Reference {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
@@ -90,26 +99,56 @@ pub struct Reference {
     pub description: Option<String>,
     #[serde(rename = "$ref")]
     pub ref_field: String,
+    #[serde(flatten)]
+    pub annotations: Annotations,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
 pub struct OneOf {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     pub one_of: Vec<Schema>,
+    #[serde(flatten)]
+    pub annotations: Annotations,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
 pub struct AllOf {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     pub all_of: Vec<Schema>,
+    #[serde(flatten)]
+    pub annotations: Annotations,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnyOf {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub any_of: Vec<Schema>,
+    #[serde(flatten)]
+    pub annotations: Annotations,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Not {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub not: Box<Schema>,
+    #[serde(flatten)]
+    pub annotations: Annotations,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -118,42 +157,91 @@ pub enum Primitive {
     Array(ArrayPrimitive),
     Boolean(BooleanPrimitive),
     Integer(IntegerPrimitive),
+    Number(NumberPrimitive),
+    Null(NullPrimitive),
     Object(ObjectPrimitive),
     String(StringPrimitive),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
 pub struct ArrayPrimitive {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     pub items: Box<Schema>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<u64>,
+    #[serde(flatten)]
+    pub annotations: Annotations,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
 pub struct BooleanPrimitive {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#const: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#enum: Option<Vec<bool>>,
+    #[serde(flatten)]
+    pub annotations: Annotations,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
 pub struct IntegerPrimitive {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Bounds beyond 64 bits are parsed as `f64`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<serde_json::Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<serde_json::Number>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub minimum: Option<i32>,
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#const: Option<i64>,
+    #[serde(flatten)]
+    pub annotations: Annotations,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct NumberPrimitive {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+    #[serde(flatten)]
+    pub annotations: Annotations,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NullPrimitive {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub annotations: Annotations,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ObjectPrimitive {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
@@ -161,13 +249,25 @@ pub struct ObjectPrimitive {
     pub description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub properties: IndexMap<String, Schema>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub required: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_properties: Option<AdditionalProperties>,
+    #[serde(flatten)]
+    pub annotations: Annotations,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[serde(untagged)]
+pub enum AdditionalProperties {
+    Allowed(bool),
+    Schema(Box<Schema>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct StringPrimitive {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
@@ -180,8 +280,31 @@ pub struct StringPrimitive {
     pub r#enum: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pattern: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#const: Option<String>,
+    #[serde(flatten)]
+    pub annotations: Annotations,
 }
 
+/// Keywords allowed on any schema that don't affect its shape.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotations {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub examples: Option<Vec<serde_json::Value>>,
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+/// Specification extensions (`x-*` keywords). Any other unknown keyword is rejected.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct Extensions(pub IndexMap<String, serde_json::Value>);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ErrorType {
@@ -204,6 +327,8 @@ impl Schema {
             Self::Ref(schema) => schema.title.as_ref(),
             Self::OneOf(schema) => schema.title.as_ref(),
             Self::AllOf(schema) => schema.title.as_ref(),
+            Self::AnyOf(schema) => schema.title.as_ref(),
+            Self::Not(schema) => schema.title.as_ref(),
             Self::Primitive(schema) => schema.title(),
         }
     }
@@ -213,6 +338,8 @@ impl Schema {
             Self::Ref(schema) => schema.description.as_ref(),
             Self::OneOf(schema) => schema.description.as_ref(),
             Self::AllOf(schema) => schema.description.as_ref(),
+            Self::AnyOf(schema) => schema.description.as_ref(),
+            Self::Not(schema) => schema.description.as_ref(),
             Self::Primitive(schema) => schema.description(),
         }
     }
@@ -222,9 +349,50 @@ impl Schema {
             Self::Ref(_) => None,
             Self::OneOf(_) => None,
             Self::AllOf(_) => None,
+            Self::AnyOf(_) => None,
+            Self::Not(_) => None,
             Self::Primitive(schema) => schema.summary(),
         }
     }
+
+    pub fn annotations(&self) -> &Annotations {
+        match self {
+            Self::Ref(schema) => &schema.annotations,
+            Self::OneOf(schema) => &schema.annotations,
+            Self::AllOf(schema) => &schema.annotations,
+            Self::AnyOf(schema) => &schema.annotations,
+            Self::Not(schema) => &schema.annotations,
+            Self::Primitive(schema) => schema.annotations(),
+        }
+    }
+
+    /// Names of the schemas values can hold, without following the references. `not` is skipped
+    /// as it only restricts values.
+    pub fn references(&self) -> Vec<&str> {
+        match self {
+            Self::Ref(reference) => vec![reference.name()],
+            Self::OneOf(OneOf { one_of: items, .. })
+            | Self::AllOf(AllOf { all_of: items, .. })
+            | Self::AnyOf(AnyOf { any_of: items, .. }) => {
+                items.iter().flat_map(|item| item.references()).collect()
+            }
+            Self::Not(_) => vec![],
+            Self::Primitive(Primitive::Array(array)) => array.items.references(),
+            Self::Primitive(Primitive::Object(object)) => {
+                let additional_properties = match &object.additional_properties {
+                    Some(AdditionalProperties::Schema(schema)) => Some(schema.as_ref()),
+                    _ => None,
+                };
+                object
+                    .properties
+                    .values()
+                    .chain(additional_properties)
+                    .flat_map(|item| item.references())
+                    .collect()
+            }
+            Self::Primitive(_) => vec![],
+        }
+    }
 }
 
 impl Primitive {
@@ -233,6 +401,8 @@ impl Primitive {
             Self::Array(schema) => schema.title.as_ref(),
             Self::Boolean(schema) => schema.title.as_ref(),
             Self::Integer(schema) => schema.title.as_ref(),
+            Self::Number(schema) => schema.title.as_ref(),
+            Self::Null(schema) => schema.title.as_ref(),
             Self::Object(schema) => schema.title.as_ref(),
             Self::String(schema) => schema.title.as_ref(),
         }
@@ -243,6 +413,8 @@ impl Primitive {
             Self::Array(schema) => schema.description.as_ref(),
             Self::Boolean(schema) => schema.description.as_ref(),
             Self::Integer(schema) => schema.description.as_ref(),
+            Self::Number(schema) => schema.description.as_ref(),
+            Self::Null(schema) => schema.description.as_ref(),
             Self::Object(schema) => schema.description.as_ref(),
             Self::String(schema) => schema.description.as_ref(),
         }
@@ -253,10 +425,24 @@ impl Primitive {
             Self::Array(_) => None,
             Self::Boolean(_) => None,
             Self::Integer(_) => None,
+            Self::Number(_) => None,
+            Self::Null(_) => None,
             Self::Object(schema) => schema.summary.as_ref(),
             Self::String(_) => None,
         }
     }
+
+    pub fn annotations(&self) -> &Annotations {
+        match self {
+            Self::Array(schema) => &schema.annotations,
+            Self::Boolean(schema) => &schema.annotations,
+            Self::Integer(schema) => &schema.annotations,
+            Self::Number(schema) => &schema.annotations,
+            Self::Null(schema) => &schema.annotations,
+            Self::Object(schema) => &schema.annotations,
+            Self::String(schema) => &schema.annotations,
+        }
+    }
 }
 
 impl Reference {
@@ -267,3 +453,15 @@ impl Reference {
         }
     }
 }
+
+impl<'de> Deserialize<'de> for Extensions {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = IndexMap::<String, serde_json::Value>::deserialize(deserializer)?;
+
+        if let Some(key) = fields.keys().find(|key| !key.starts_with("x-")) {
+            return Err(serde::de::Error::custom(format!("unknown field `{key}`")));
+        }
+
+        Ok(Self(fields))
+    }
+}
@@ -0,0 +1,581 @@
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+
+use super::generate::{method_type_name, parse_merged_specs, rust_type_name, to_variant_name};
+use crate::{spec::*, GenerationProfile, ProfileOptions, SpecVersion};
+
+#[derive(Debug, Parser)]
+pub struct Docs {
+    #[clap(long, env, help = "Version of the specification")]
+    spec: SpecVersion,
+    #[clap(long, value_enum, default_value_t = DocsFormat::Markdown, help = "Output format")]
+    format: DocsFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DocsFormat {
+    Markdown,
+    Html,
+}
+
+/// Format-agnostic document, rendered into Markdown or HTML.
+enum Block {
+    Heading {
+        level: usize,
+        anchor: Option<String>,
+        content: Vec<Inline>,
+    },
+    Paragraph(Vec<Inline>),
+    List(Vec<Vec<Inline>>),
+    Table {
+        header: Vec<&'static str>,
+        rows: Vec<Vec<Vec<Inline>>>,
+    },
+}
+
+#[derive(Clone)]
+enum Inline {
+    Text(String),
+    Code(String),
+    Strong(String),
+    Link {
+        content: Box<Inline>,
+        anchor: String,
+    },
+}
+
+impl Docs {
+    pub(crate) fn run(self, profiles: &[GenerationProfile]) -> Result<()> {
+        let profile = profiles
+            .iter()
+            .find(|profile| profile.version == self.spec)
+            .expect("Unable to find profile");
+
+        let specs = parse_merged_specs(profile);
+
+        let blocks = document_blocks(&specs, &profile.options);
+
+        match self.format {
+            DocsFormat::Markdown => render_markdown_stdout(&blocks),
+            DocsFormat::Html => render_html_stdout(&specs.info.title, &blocks),
+        }
+
+        Ok(())
+    }
+}
+
+fn document_blocks(specs: &Specification, options: &ProfileOptions) -> Vec<Block> {
+    let mut blocks = vec![
+        Block::Heading {
+            level: 1,
+            anchor: None,
+            content: vec![Inline::Text(format!(
+                "{} v{}",
+                specs.info.title, specs.info.version
+            ))],
+        },
+        Block::Heading {
+            level: 2,
+            anchor: None,
+            content: vec![Inline::Text(String::from("Methods"))],
+        },
+    ];
+
+    for method in specs.methods.iter() {
+        blocks.push(Block::Heading {
+            level: 3,
+            anchor: Some(method_anchor(&method.name)),
+            content: vec![Inline::Code(method.name.clone())],
+        });
+        if method.deprecated {
+            blocks.push(Block::Paragraph(vec![Inline::Strong(String::from(
+                "Deprecated",
+            ))]));
+        }
+        blocks.push(Block::Paragraph(vec![Inline::Text(method.summary.clone())]));
+        if let Some(description) = &method.description {
+            blocks.push(Block::Paragraph(vec![Inline::Text(description.clone())]));
+        }
+        blocks.push(Block::Paragraph(vec![
+            Inline::Text(String::from("Rust types: ")),
+            Inline::Code(method_type_name(&method.name, "Request")),
+            Inline::Text(String::from(", ")),
+            Inline::Code(method_type_name(&method.name, "Response")),
+        ]));
+
+        if !method.params.is_empty() {
+            blocks.push(Block::Paragraph(vec![Inline::Strong(String::from(
+                "Params",
+            ))]));
+            blocks.push(Block::Table {
+                header: vec!["Name", "Type", "Required", "Description"],
+                rows: method
+                    .params
+                    .iter()
+                    .map(|param| {
+                        vec![
+                            vec![Inline::Code(param.name.clone())],
+                            schema_type(&param.schema),
+                            vec![Inline::Text(yes_no(param.required))],
+                            description_inlines(
+                                param.description.as_ref().or(param.summary.as_ref()),
+                                param.deprecated,
+                            ),
+                        ]
+                    })
+                    .collect(),
+            });
+        }
+
+        blocks.push(Block::Paragraph(vec![Inline::Strong(String::from(
+            "Result",
+        ))]));
+        blocks.push(Block::Table {
+            header: vec!["Name", "Type", "Description"],
+            rows: vec![vec![
+                vec![Inline::Code(method.result.name.clone())],
+                schema_type(&method.result.schema),
+                description_inlines(
+                    method
+                        .result
+                        .description
+                        .as_ref()
+                        .or(method.result.summary.as_ref()),
+                    false,
+                ),
+            ]],
+        });
+
+        if let Some(errors) = method.errors.as_ref().filter(|errors| !errors.is_empty()) {
+            blocks.push(Block::Paragraph(vec![Inline::Strong(String::from(
+                "Errors",
+            ))]));
+            blocks.push(Block::List(
+                errors
+                    .iter()
+                    .map(|error| {
+                        let mut content = vec![error_link(error.name())];
+                        if let Some(ErrorType::Error(error)) =
+                            specs.components.errors.get(error.name())
+                        {
+                            content.push(Inline::Text(format!(" ({})", error.code)));
+                        }
+                        content
+                    })
+                    .collect(),
+            ));
+        }
+    }
+
+    blocks.push(Block::Heading {
+        level: 2,
+        anchor: None,
+        content: vec![Inline::Text(String::from("Schemas"))],
+    });
+
+    for (name, schema) in specs.components.schemas.iter() {
+        blocks.push(Block::Heading {
+            level: 3,
+            anchor: Some(schema_anchor(name)),
+            content: vec![Inline::Code(name.clone())],
+        });
+        if schema.annotations().deprecated {
+            blocks.push(Block::Paragraph(vec![Inline::Strong(String::from(
+                "Deprecated",
+            ))]));
+        }
+        for doc in [schema.title(), schema.description().or(schema.summary())]
+            .into_iter()
+            .flatten()
+        {
+            blocks.push(Block::Paragraph(vec![Inline::Text(doc.clone())]));
+        }
+        blocks.push(Block::Paragraph(vec![
+            Inline::Text(String::from("Rust type: ")),
+            Inline::Code(rust_type_name(name, options)),
+        ]));
+
+        blocks.append(&mut schema_blocks(schema));
+    }
+
+    blocks.push(Block::Heading {
+        level: 2,
+        anchor: None,
+        content: vec![Inline::Text(String::from("Errors"))],
+    });
+    for (name, error) in specs.components.errors.iter() {
+        blocks.push(Block::Heading {
+            level: 3,
+            anchor: Some(error_anchor(name)),
+            content: vec![Inline::Code(name.clone())],
+        });
+        match error {
+            ErrorType::Error(error) => {
+                blocks.push(Block::Paragraph(vec![Inline::Text(error.message.clone())]));
+                blocks.push(Block::Paragraph(vec![
+                    Inline::Text(String::from("Code: ")),
+                    Inline::Code(error.code.to_string()),
+                ]));
+                if let Some(data) = &error.data {
+                    blocks.push(Block::Paragraph(
+                        [
+                            vec![Inline::Text(String::from("Data: "))],
+                            schema_type(data),
+                        ]
+                        .concat(),
+                    ));
+                }
+            }
+            ErrorType::Reference(reference) => blocks.push(Block::Paragraph(vec![
+                Inline::Text(String::from("Same as ")),
+                error_link(reference.name()),
+            ])),
+        }
+        blocks.push(Block::Paragraph(vec![
+            Inline::Text(String::from("Rust variant: ")),
+            Inline::Code(format!(
+                "StarknetError::{}",
+                to_variant_name("StarknetError", name, &options.naming)
+            )),
+        ]));
+    }
+
+    blocks
+}
+
+/// Details of a schema beyond its type, like object fields and union variants.
+fn schema_blocks(schema: &Schema) -> Vec<Block> {
+    match schema {
+        Schema::Primitive(Primitive::Object(object)) => vec![fields_table(object)],
+        Schema::AllOf(all_of) => {
+            let mut blocks = vec![Block::Paragraph(vec![Inline::Text(String::from(
+                "All of:",
+            ))])];
+            for item in all_of.all_of.iter() {
+                match item {
+                    Schema::Primitive(Primitive::Object(object)) => {
+                        blocks.push(fields_table(object))
+                    }
+                    _ => blocks.push(Block::List(vec![schema_type(item)])),
+                }
+            }
+            blocks
+        }
+        Schema::OneOf(OneOf { one_of: items, .. }) | Schema::AnyOf(AnyOf { any_of: items, .. }) => {
+            let mut blocks = vec![Block::Paragraph(vec![Inline::Text(String::from(
+                if matches!(schema, Schema::OneOf(_)) {
+                    "One of:"
+                } else {
+                    "Any of:"
+                },
+            ))])];
+            blocks.push(Block::List(
+                items
+                    .iter()
+                    .map(|item| {
+                        let mut content = schema_type(item);
+                        if let (Schema::Primitive(_), Some(title)) = (item, item.title()) {
+                            content.push(Inline::Text(format!(" ({title})")));
+                        }
+                        content
+                    })
+                    .collect(),
+            ));
+            for item in items.iter() {
+                if let Schema::Primitive(Primitive::Object(object)) = item {
+                    if let Some(title) = &object.title {
+                        blocks.push(Block::Paragraph(vec![Inline::Strong(title.clone())]));
+                    }
+                    blocks.push(fields_table(object));
+                }
+            }
+            blocks
+        }
+        _ => vec![Block::Paragraph(
+            [
+                vec![Inline::Text(String::from("Type: "))],
+                schema_type(schema),
+            ]
+            .concat(),
+        )],
+    }
+}
+
+fn fields_table(object: &ObjectPrimitive) -> Block {
+    let required = object.required.as_deref().unwrap_or_default();
+    let mut rows = object
+        .properties
+        .iter()
+        .map(|(name, property)| {
+            vec![
+                vec![Inline::Code(name.clone())],
+                schema_type(property),
+                vec![Inline::Text(yes_no(required.contains(name)))],
+                description_inlines(
+                    property.description().or(property.title()),
+                    property.annotations().deprecated,
+                ),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(AdditionalProperties::Schema(value)) = &object.additional_properties {
+        rows.push(vec![
+            vec![Inline::Text(String::from("Any other key"))],
+            schema_type(value),
+            vec![Inline::Text(yes_no(false))],
+            vec![],
+        ]);
+    }
+
+    Block::Table {
+        header: vec!["Field", "Type", "Required", "Description"],
+        rows,
+    }
+}
+
+/// Short description of a schema type, linking to referenced schemas.
+fn schema_type(schema: &Schema) -> Vec<Inline> {
+    match schema {
+        Schema::Ref(reference) => vec![schema_link(reference.name())],
+        Schema::OneOf(value) => combined_type("one of", &value.one_of),
+        Schema::AllOf(value) => combined_type("all of", &value.all_of),
+        Schema::AnyOf(value) => combined_type("any of", &value.any_of),
+        Schema::Not(value) => [
+            vec![Inline::Text(String::from("not "))],
+            schema_type(&value.not),
+        ]
+        .concat(),
+        Schema::Primitive(Primitive::Array(value)) => [
+            vec![Inline::Text(String::from("array of "))],
+            schema_type(&value.items),
+        ]
+        .concat(),
+        Schema::Primitive(Primitive::Boolean(value)) => match value.r#const {
+            Some(value) => vec![Inline::Code(value.to_string())],
+            None => vec![Inline::Text(String::from("boolean"))],
+        },
+        Schema::Primitive(Primitive::Integer(value)) => match value.r#const {
+            Some(value) => vec![Inline::Code(value.to_string())],
+            None => vec![Inline::Text(String::from("integer"))],
+        },
+        Schema::Primitive(Primitive::Number(_)) => vec![Inline::Text(String::from("number"))],
+        Schema::Primitive(Primitive::Null(_)) => vec![Inline::Text(String::from("null"))],
+        Schema::Primitive(Primitive::Object(_)) => vec![Inline::Text(String::from("object"))],
+        Schema::Primitive(Primitive::String(value)) => {
+            match (&value.r#const, &value.r#enum, &value.pattern) {
+                (Some(value), _, _) => vec![Inline::Code(format!("{value:?}"))],
+                (None, Some(values), _) => {
+                    let mut inlines = vec![];
+                    for (ind, value) in values.iter().enumerate() {
+                        if ind != 0 {
+                            inlines.push(Inline::Text(String::from(" | ")));
+                        }
+                        inlines.push(Inline::Code(format!("{value:?}")));
+                    }
+                    inlines
+                }
+                (None, None, Some(pattern)) => vec![
+                    Inline::Text(String::from("string matching ")),
+                    Inline::Code(pattern.clone()),
+                ],
+                (None, None, None) => vec![Inline::Text(String::from("string"))],
+            }
+        }
+    }
+}
+
+fn combined_type(label: &str, items: &[Schema]) -> Vec<Inline> {
+    let mut inlines = vec![Inline::Text(format!("{label} "))];
+    for (ind, item) in items.iter().enumerate() {
+        if ind != 0 {
+            inlines.push(Inline::Text(String::from(", ")));
+        }
+        inlines.append(&mut schema_type(item));
+    }
+    inlines
+}
+
+fn description_inlines(description: Option<&String>, deprecated: bool) -> Vec<Inline> {
+    let mut inlines = vec![];
+    if deprecated {
+        inlines.push(Inline::Strong(String::from("Deprecated.")));
+        if description.is_some() {
+            inlines.push(Inline::Text(String::from(" ")));
+        }
+    }
+    if let Some(description) = description {
+        inlines.push(Inline::Text(description.clone()));
+    }
+    inlines
+}
+
+fn schema_link(name: &str) -> Inline {
+    Inline::Link {
+        content: Box::new(Inline::Code(name.to_owned())),
+        anchor: schema_anchor(name),
+    }
+}
+
+fn error_link(name: &str) -> Inline {
+    Inline::Link {
+        content: Box::new(Inline::Code(name.to_owned())),
+        anchor: error_anchor(name),
+    }
+}
+
+fn method_anchor(name: &str) -> String {
+    format!("method-{name}")
+}
+
+fn schema_anchor(name: &str) -> String {
+    format!("schema-{name}")
+}
+
+fn error_anchor(name: &str) -> String {
+    format!("error-{name}")
+}
+
+fn yes_no(value: bool) -> String {
+    String::from(if value { "Yes" } else { "No" })
+}
+
+fn render_markdown_stdout(blocks: &[Block]) {
+    for (ind, block) in blocks.iter().enumerate() {
+        if ind != 0 {
+            println!();
+        }
+
+        match block {
+            Block::Heading {
+                level,
+                anchor,
+                content,
+            } => {
+                if let Some(anchor) = anchor {
+                    println!("<a id=\"{anchor}\"></a>");
+                    println!();
+                }
+                println!("{} {}", "#".repeat(*level), markdown_inlines(content));
+            }
+            Block::Paragraph(content) => println!("{}", markdown_inlines(content)),
+            Block::List(items) => {
+                for item in items.iter() {
+                    println!("- {}", markdown_inlines(item));
+                }
+            }
+            Block::Table { header, rows } => {
+                println!("| {} |", header.join(" | "));
+                println!("|{}", " --- |".repeat(header.len()));
+                for row in rows.iter() {
+                    println!(
+                        "| {} |",
+                        row.iter()
+                            .map(|cell| markdown_inlines(cell).replace('|', "\\|"))
+                            .collect::<Vec<_>>()
+                            .join(" | ")
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn markdown_inlines(inlines: &[Inline]) -> String {
+    inlines.iter().map(markdown_inline).collect()
+}
+
+fn markdown_inline(inline: &Inline) -> String {
+    match inline {
+        // Descriptions are kept as-is, as they sometimes contain Markdown already
+        Inline::Text(text) => text.replace('\n', " "),
+        Inline::Code(code) => format!("`{code}`"),
+        Inline::Strong(text) => format!("**{text}**"),
+        Inline::Link { content, anchor } => format!("[{}](#{anchor})", markdown_inline(content)),
+    }
+}
+
+fn render_html_stdout(title: &str, blocks: &[Block]) {
+    println!("<!DOCTYPE html>");
+    println!("<html>");
+    println!("<head>");
+    println!("<meta charset=\"utf-8\">");
+    println!("<title>{}</title>", escape_html(title));
+    println!("<style>");
+    println!("table {{ border-collapse: collapse; }}");
+    println!("th, td {{ border: 1px solid #ccc; padding: 4px 8px; text-align: left; }}");
+    println!("</style>");
+    println!("</head>");
+    println!("<body>");
+
+    for block in blocks.iter() {
+        match block {
+            Block::Heading {
+                level,
+                anchor,
+                content,
+            } => println!(
+                "<h{level}{}>{}</h{level}>",
+                anchor
+                    .as_ref()
+                    .map(|anchor| format!(" id=\"{}\"", escape_html(anchor)))
+                    .unwrap_or_default(),
+                html_inlines(content)
+            ),
+            Block::Paragraph(content) => println!("<p>{}</p>", html_inlines(content)),
+            Block::List(items) => {
+                println!("<ul>");
+                for item in items.iter() {
+                    println!("<li>{}</li>", html_inlines(item));
+                }
+                println!("</ul>");
+            }
+            Block::Table { header, rows } => {
+                println!("<table>");
+                println!(
+                    "<tr>{}</tr>",
+                    header
+                        .iter()
+                        .map(|cell| format!("<th>{}</th>", escape_html(cell)))
+                        .collect::<String>()
+                );
+                for row in rows.iter() {
+                    println!(
+                        "<tr>{}</tr>",
+                        row.iter()
+                            .map(|cell| format!("<td>{}</td>", html_inlines(cell)))
+                            .collect::<String>()
+                    );
+                }
+                println!("</table>");
+            }
+        }
+    }
+
+    println!("</body>");
+    println!("</html>");
+}
+
+fn html_inlines(inlines: &[Inline]) -> String {
+    inlines.iter().map(html_inline).collect()
+}
+
+fn html_inline(inline: &Inline) -> String {
+    match inline {
+        Inline::Text(text) => escape_html(text),
+        Inline::Code(code) => format!("<code>{}</code>", escape_html(code)),
+        Inline::Strong(text) => format!("<strong>{}</strong>", escape_html(text)),
+        Inline::Link { content, anchor } => format!(
+            "<a href=\"#{}\">{}</a>",
+            escape_html(anchor),
+            html_inline(content)
+        ),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
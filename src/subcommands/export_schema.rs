@@ -0,0 +1,289 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use indexmap::IndexMap;
+use serde_json::{Map, Value};
+
+use crate::{GenerationProfile, SpecVersion};
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+#[derive(Debug, Parser)]
+pub struct ExportSchema {
+    #[clap(long, env, help = "Version of the specification")]
+    spec: SpecVersion,
+}
+
+/// OpenRPC documents making up a specification version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Document {
+    Main,
+    Write,
+    Trace,
+}
+
+/// Components of all documents merged into a single set of definitions.
+struct Definitions {
+    documents: IndexMap<Document, Value>,
+    /// Names in the bundle of schemas defined in each document. Schemas only referencing a schema
+    /// from another document aren't listed, as they resolve to the referenced schema instead.
+    names: HashMap<(Document, String), String>,
+    /// Source of each definition in the bundle.
+    sources: HashMap<String, (Document, String)>,
+}
+
+impl ExportSchema {
+    pub(crate) fn run(self, profiles: &[GenerationProfile]) -> Result<()> {
+        let profile = profiles
+            .iter()
+            .find(|profile| profile.version == self.spec)
+            .expect("Unable to find profile");
+
+        let mut documents = IndexMap::new();
+        for (document, raw_specs) in [
+            (Document::Main, profile.raw_specs.main),
+            (Document::Write, profile.raw_specs.write),
+            (Document::Trace, profile.raw_specs.trace),
+        ] {
+            documents.insert(
+                document,
+                serde_json::from_str::<Value>(raw_specs).expect("Failed to parse specification"),
+            );
+        }
+
+        let definitions = Definitions::new(documents)?;
+
+        let mut bundles = Map::new();
+        for (document, specs) in definitions.documents.iter() {
+            for method in get_array(specs, "methods")?.iter() {
+                let method_name = get_str(method, "name")?;
+
+                // Bundles that can't be built are skipped, as some specs reference schemas that
+                // don't exist
+                let mut params = Map::new();
+                for param in get_array(method, "params")?.iter() {
+                    let param_name = get_str(param, "name")?;
+                    match definitions.bundle(*document, get_value(param, "schema")?) {
+                        Ok(param_bundle) => {
+                            params.insert(param_name.to_owned(), param_bundle);
+                        }
+                        Err(err) => {
+                            eprintln!("Param {param_name} of {method_name} not exported: {err:#}")
+                        }
+                    }
+                }
+
+                let mut bundle = Map::new();
+                bundle.insert(String::from("params"), Value::Object(params));
+                match definitions.bundle(
+                    *document,
+                    get_value(get_value(method, "result")?, "schema")?,
+                ) {
+                    Ok(result_bundle) => {
+                        bundle.insert(String::from("result"), result_bundle);
+                    }
+                    Err(err) => eprintln!("Result of {method_name} not exported: {err:#}"),
+                }
+                bundles.insert(method_name.to_owned(), Value::Object(bundle));
+            }
+        }
+
+        println!("{}", serde_json::to_string_pretty(&bundles)?);
+
+        Ok(())
+    }
+}
+
+impl Document {
+    fn from_file_name(path: &str) -> Result<Self> {
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+        Ok(match file_name {
+            "starknet_api_openrpc.json" => Self::Main,
+            "starknet_write_api.json" => Self::Write,
+            "starknet_trace_api_openrpc.json" => Self::Trace,
+            _ => anyhow::bail!("Unknown specification document: {path}"),
+        })
+    }
+
+    /// Prepended to the names of definitions clashing with ones from other documents.
+    fn prefix(&self) -> &'static str {
+        match self {
+            Self::Main => "MAIN",
+            Self::Write => "WRITE",
+            Self::Trace => "TRACE",
+        }
+    }
+}
+
+impl Definitions {
+    fn new(documents: IndexMap<Document, Value>) -> Result<Self> {
+        let mut names = HashMap::new();
+        let mut sources: HashMap<String, (Document, String)> = HashMap::new();
+
+        for (document, specs) in documents.iter() {
+            for (name, schema) in get_schemas(specs)?.iter() {
+                if is_external_alias(schema) {
+                    continue;
+                }
+
+                // Identical copies across documents share a single definition
+                let bundle_name = match sources.get(name) {
+                    Some((existing_document, _))
+                        if get_schemas(&documents[existing_document])?.get(name)
+                            == Some(schema) =>
+                    {
+                        name.to_owned()
+                    }
+                    Some(_) => format!("{}_{}", document.prefix(), name),
+                    None => name.to_owned(),
+                };
+                sources
+                    .entry(bundle_name.clone())
+                    .or_insert_with(|| (*document, name.to_owned()));
+                names.insert((*document, name.to_owned()), bundle_name);
+            }
+        }
+
+        Ok(Self {
+            documents,
+            names,
+            sources,
+        })
+    }
+
+    /// Builds a standalone schema, with every schema it references directly or indirectly under
+    /// `$defs`.
+    fn bundle(&self, document: Document, schema: &Value) -> Result<Value> {
+        let mut queue = VecDeque::new();
+
+        let mut root = schema.clone();
+        self.rewrite_refs(document, &mut root, &mut queue)?;
+
+        let mut defs = IndexMap::new();
+        while let Some(name) = queue.pop_front() {
+            if defs.contains_key(&name) {
+                continue;
+            }
+
+            let (source_document, source_name) = &self.sources[&name];
+            let mut def = get_schemas(&self.documents[source_document])?[source_name].clone();
+            self.rewrite_refs(*source_document, &mut def, &mut queue)
+                .with_context(|| format!("Unable to bundle {source_name}"))?;
+            defs.insert(name, def);
+        }
+        defs.sort_keys();
+
+        let mut bundle = Map::new();
+        bundle.insert(
+            String::from("$schema"),
+            Value::String(JSON_SCHEMA_DIALECT.to_owned()),
+        );
+        match root {
+            Value::Object(root) => bundle.extend(root),
+            _ => anyhow::bail!("Schema is not an object"),
+        }
+        if !defs.is_empty() {
+            bundle.insert(
+                String::from("$defs"),
+                Value::Object(defs.into_iter().collect()),
+            );
+        }
+
+        Ok(Value::Object(bundle))
+    }
+
+    /// Points references into the `$defs` of the bundle, queueing the definitions referenced.
+    fn rewrite_refs(
+        &self,
+        document: Document,
+        schema: &mut Value,
+        queue: &mut VecDeque<String>,
+    ) -> Result<()> {
+        match schema {
+            Value::Object(object) => {
+                if let Some(Value::String(reference)) = object.get_mut("$ref") {
+                    let name = self.resolve_ref(document, reference)?;
+                    *reference = format!("#/$defs/{name}");
+                    queue.push_back(name);
+                }
+                for value in object.values_mut() {
+                    self.rewrite_refs(document, value, queue)?;
+                }
+            }
+            Value::Array(array) => {
+                for value in array.iter_mut() {
+                    self.rewrite_refs(document, value, queue)?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Name in the bundle of the schema a reference points to, following references to schemas
+    /// in other documents.
+    fn resolve_ref(&self, document: Document, reference: &str) -> Result<String> {
+        let (path, pointer) = reference
+            .split_once('#')
+            .ok_or_else(|| anyhow::anyhow!("Unsupported reference: {reference}"))?;
+        let name = pointer
+            .strip_prefix("/components/schemas/")
+            .ok_or_else(|| anyhow::anyhow!("Unsupported reference: {reference}"))?;
+        let document = if path.is_empty() {
+            document
+        } else {
+            Document::from_file_name(path)?
+        };
+
+        if let Some(bundle_name) = self.names.get(&(document, name.to_owned())) {
+            return Ok(bundle_name.to_owned());
+        }
+
+        match get_schemas(&self.documents[&document])?.get(name) {
+            Some(schema) if is_external_alias(schema) => {
+                self.resolve_ref(document, get_str(schema, "$ref")?)
+            }
+            _ => anyhow::bail!("Unresolved reference: {reference}"),
+        }
+    }
+}
+
+/// Whether a schema is only a reference to a schema from another document.
+fn is_external_alias(schema: &Value) -> bool {
+    match schema.as_object() {
+        Some(object) => {
+            object.len() == 1
+                && object
+                    .get("$ref")
+                    .and_then(|reference| reference.as_str())
+                    .is_some_and(|reference| !reference.starts_with('#'))
+        }
+        None => false,
+    }
+}
+
+fn get_schemas(specs: &Value) -> Result<&Map<String, Value>> {
+    get_value(get_value(specs, "components")?, "schemas")?
+        .as_object()
+        .ok_or_else(|| anyhow::anyhow!("`schemas` is not an object"))
+}
+
+fn get_value<'a>(value: &'a Value, key: &str) -> Result<&'a Value> {
+    value
+        .get(key)
+        .ok_or_else(|| anyhow::anyhow!("Missing `{key}`"))
+}
+
+fn get_array<'a>(value: &'a Value, key: &str) -> Result<&'a Vec<Value>> {
+    get_value(value, key)?
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("`{key}` is not an array"))
+}
+
+fn get_str<'a>(value: &'a Value, key: &str) -> Result<&'a str> {
+    get_value(value, key)?
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("`{key}` is not a string"))
+}
@@ -1,27 +1,90 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::PathBuf,
+};
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result};
+use clap::{Parser, ValueEnum};
+use indexmap::IndexMap;
 use regex::Regex;
 
 use crate::{
-    built_info, spec::*, ArcWrappingOptions, FixedField, FixedFieldsOptions, FlattenOption,
-    GenerationProfile, SpecVersion,
+    built_info, spec::*, CaseStrategy, FieldWrapper, FieldWrappingOptions, FixedField,
+    FlattenOption, GenerationProfile, NamingOptions, ProfileOptions, QueryVersionOptions,
+    SpecVersion, TypeConversion,
 };
 
+mod proto;
+use proto::ProtoLock;
+
+mod lint;
+
+mod parity;
+
+mod pattern;
+use pattern::PatternDfa;
+
+mod typescript;
+
 #[derive(Debug, Parser)]
 pub struct Generate {
     #[clap(long, env, help = "Version of the specification")]
     spec: SpecVersion,
+    #[clap(long, value_enum, default_value_t = Language::Rust, help = "Language of the generated code")]
+    lang: Language,
+    #[clap(
+        long,
+        required_if_eq("lang", "proto"),
+        help = "Lock file keeping field numbers stable, read and updated when generating proto files"
+    )]
+    proto_lock: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Language {
+    Rust,
+    Typescript,
+    Proto,
 }
 
 const MAX_LINE_LENGTH: usize = 100;
 
+/// Field collecting the `additionalProperties` of an object.
+const ADDITIONAL_PROPERTIES_FIELD: &str = "additional_properties";
+
+/// Strict and reserved keywords of all editions.
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Keywords that can't be used as raw identifiers.
+const NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
 #[derive(Debug, Clone)]
 struct TypeResolutionResult {
     model_types: Vec<RustType>,
     request_response_types: Vec<RustType>,
+    fragment_traits: Vec<RustTrait>,
     not_implemented: Vec<String>,
+    /// Ignored and not implemented schemas that must be generic over `'a`, as they hold borrowing
+    /// types.
+    borrowing_manual_types: HashSet<String>,
+}
+
+/// Trait with accessors for the fields of a flattened schema, implemented by the structs it's
+/// flattened into.
+#[derive(Debug, Clone)]
+struct RustTrait {
+    name: String,
+    title: Option<String>,
+    fragment: String,
+    fields: Vec<RustField>,
+    /// Implementing structs, and whether they're generic over `'a`.
+    implementors: Vec<(String, bool)>,
 }
 
 #[derive(Debug, Clone)]
@@ -29,9 +92,18 @@ struct RustType {
     title: Option<String>,
     description: Option<String>,
     name: String,
+    deprecation: Option<String>,
+    extra_attributes: ExtraAttributes,
     content: RustTypeKind,
 }
 
+/// Derives and attributes requested in the profile on top of the ones a type needs.
+#[derive(Debug, Clone, Default)]
+struct ExtraAttributes {
+    derives: Vec<String>,
+    attributes: Vec<String>,
+}
+
 #[allow(unused)]
 #[derive(Debug, Clone)]
 enum RustTypeKind {
@@ -39,24 +111,64 @@ enum RustTypeKind {
     Enum(RustEnum),
     Wrapper(RustWrapper),
     Unit(RustUnit),
+    PatternString(RustPatternString),
+    Constant(RustConstant),
 }
 
 #[derive(Debug, Clone)]
 struct RustStruct {
     serde_as_array: bool,
     extra_ref_type: bool,
+    builder: bool,
+    /// Set when any field borrows, making the struct generic over `'a`.
+    lifetime: bool,
     fields: Vec<RustField>,
+    conversions: Vec<RustConversion>,
+}
+
+/// Conversion from a struct into another one.
+#[derive(Debug, Clone)]
+struct RustConversion {
+    target: String,
+    /// A `From` impl is generated when there's no method name.
+    method: Option<String>,
+    /// Parameters for target fields missing from the source, as `name: Type`.
+    params: Vec<String>,
+    fields: Vec<(String, ConversionValue)>,
+    /// Whether the source has an `is_query` flag the target can't hold.
+    drops_query: bool,
+    /// Whether the target borrows, with the same lifetime as the source if it does too.
+    target_lifetime: bool,
+}
+
+#[derive(Debug, Clone)]
+enum ConversionValue {
+    /// Moved from the source field with the same name, through `method` if any.
+    Moved {
+        wrapper: Option<FieldWrapper>,
+        is_option: bool,
+        method: Option<&'static str>,
+    },
+    Expression(String),
+    /// Taken from the method parameter with the same name.
+    Param,
 }
 
 #[derive(Debug, Clone)]
 struct RustEnum {
     is_error: bool,
+    lifetime: bool,
     variants: Vec<RustVariant>,
+    /// Whether unknown values are kept in an `Unknown(String)` variant instead of failing
+    /// deserialization.
+    unknown_variant: bool,
 }
 
 #[derive(Debug, Clone)]
 struct RustWrapper {
     type_name: String,
+    serializer: Option<SerializerOverride>,
+    lifetime: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -64,17 +176,36 @@ struct RustUnit {
     serde_as_array: bool,
 }
 
+#[derive(Debug, Clone)]
+struct RustPatternString {
+    pattern: String,
+    dfa: PatternDfa,
+}
+
+#[derive(Debug, Clone)]
+struct RustConstant {
+    type_name: String,
+    value: String,
+}
+
 #[derive(Debug, Clone)]
 struct RustField {
     description: Option<String>,
     name: String,
     optional: bool,
     fixed: Option<FixedField>,
-    arc_wrap: bool,
+    wrapper: Option<FieldWrapper>,
     type_name: String,
     serde_rename: Option<String>,
     serde_faltten: bool,
     serializer: Option<SerializerOverride>,
+    deprecation: Option<String>,
+    /// Type used in `Ref` types instead of a reference, when the field type has its own `Ref` type.
+    ref_type: Option<String>,
+    /// Schemas flattened into the struct that the field comes from, outermost first.
+    fragments: Vec<String>,
+    /// Pattern of the inline string schema the field maps to, if any.
+    pattern: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -84,6 +215,9 @@ struct RustVariant {
     serde_name: Option<String>,
     #[allow(unused)]
     error_text: Option<String>,
+    payload: Option<RustFieldType>,
+    /// The only value accepted when deserializing a `bool` payload, if the schema allows just one.
+    bool_value: Option<bool>,
 }
 
 #[derive(Debug, Clone)]
@@ -98,6 +232,24 @@ enum SerializerOverride {
     SerdeAs(String),
 }
 
+/// [FlattenOption] with `Auto` turned into an explicit list of schemas.
+#[derive(Debug, Clone)]
+enum ResolvedFlattenOption {
+    All,
+    Selected(Vec<String>),
+}
+
+/// State shared across type resolution.
+struct ResolutionContext<'a> {
+    options: &'a ProfileOptions,
+    flatten_option: ResolvedFlattenOption,
+    /// Named types synthesized from anonymous schemas used inline by fields.
+    hoisted_schemas: IndexMap<String, serde_json::Value>,
+    hoisted_types: Vec<RustType>,
+    /// Schemas whose fields are being collected, to catch schemas flattening themselves.
+    flattening: Vec<String>,
+}
+
 impl Generate {
     pub(crate) fn run(self, profiles: &[GenerationProfile]) -> Result<()> {
         let profile = profiles
@@ -105,60 +257,62 @@ impl Generate {
             .find(|profile| profile.version == self.spec)
             .expect("Unable to find profile");
 
-        let mut specs: Specification =
-            serde_json::from_str(profile.raw_specs.main).expect("Failed to parse specification");
-
-        // Merge specs (we only care about write methods and errors at the moment as the write specs
-        // does not provide additional models).
-        let mut write_specs: Specification =
-            serde_json::from_str(profile.raw_specs.write).expect("Failed to parse specification");
-        specs.methods.append(&mut write_specs.methods);
-        write_specs
-            .components
-            .errors
-            .iter()
-            .for_each(|(key, value)| {
-                if let indexmap::map::Entry::Vacant(entry) =
-                    specs.components.errors.entry(key.to_owned())
-                {
-                    entry.insert(value.to_owned());
-                }
-            });
+        let specs = parse_merged_specs(profile);
+        let result = resolve_types(&specs, &profile.options).expect("Failed to resolve types");
 
-        println!("// AUTO-GENERATED CODE. DO NOT EDIT");
-        println!("// To change the code generated, modify the codegen tool instead:");
-        println!("//     https://github.com/xJonathanLEI/starknet-jsonrpc-codegen");
-        println!();
-        println!("// Code generated with version:");
-        match built_info::GIT_COMMIT_HASH {
-            Some(commit_hash) => println!(
-                "//     https://github.com/xJonathanLEI/starknet-jsonrpc-codegen#{commit_hash}"
-            ),
-            None => println!("    <Unable to determine Git commit hash>"),
+        for warning in result.lint_mappings(&specs, &profile.options).iter() {
+            eprintln!("Suspicious type mapping: {warning}");
+        }
+
+        print_generated_header();
+
+        match self.lang {
+            Language::Rust => {}
+            Language::Typescript => {
+                result.render_typescript_stdout(&specs, &profile.options);
+                return Ok(());
+            }
+            Language::Proto => {
+                let lock_path = self.proto_lock.expect("Missing proto lock path");
+                let mut lock: ProtoLock = match std::fs::read_to_string(&lock_path) {
+                    Ok(raw_lock) => serde_json::from_str(&raw_lock)
+                        .with_context(|| format!("Invalid proto lock file: {lock_path:?}"))?,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => ProtoLock::default(),
+                    Err(err) => return Err(err.into()),
+                };
+
+                result.render_proto_stdout(&specs, &profile.options, profile.version, &mut lock);
+
+                std::fs::write(
+                    &lock_path,
+                    format!("{}\n", serde_json::to_string_pretty(&lock)?),
+                )?;
+                return Ok(());
+            }
         }
-        println!();
 
         if !profile.options.ignore_types.is_empty() {
             println!("// These types are ignored from code generation. Implement them manually:");
             for ignored_type in profile.options.ignore_types.iter() {
-                println!("// - `{ignored_type}`");
+                println!(
+                    "// - `{ignored_type}`{}",
+                    result.lifetime_note(ignored_type)
+                );
             }
             println!();
         }
 
-        let result = resolve_types(
-            &specs,
-            &profile.options.flatten_options,
-            &profile.options.ignore_types,
-            &profile.options.fixed_field_types,
-            &profile.options.arc_wrapped_types,
-        )
-        .expect("Failed to resolve types");
+        // Generated code itself refers to deprecated items (e.g. in serde impls). Warnings are only
+        // meant for downstream users.
+        if result.has_deprecations() {
+            println!("#![allow(deprecated)]");
+            println!();
+        }
 
         if !result.not_implemented.is_empty() {
             println!("// Code generation requested but not implemented for these types:");
             for type_name in result.not_implemented.iter() {
-                println!("// - `{type_name}`");
+                println!("// - `{type_name}`{}", result.lifetime_note(type_name));
             }
             println!();
         }
@@ -199,14 +353,54 @@ impl Generate {
         println!("pub type OwnedPtr<T> = alloc::boxed::Box<T>;");
         println!();
 
+        let query_version_offset = parse_felt(&profile.options.query_version.offset)
+            .expect("Invalid query version offset");
         println!("const QUERY_VERSION_OFFSET: FieldElement = FieldElement::from_mont([");
-        println!("    18446744073700081665,");
-        println!("    17407,");
-        println!("    18446744073709551584,");
-        println!("    576460752142434320,");
+        for limb in felt_to_mont(query_version_offset).iter() {
+            println!("    {limb},");
+        }
         println!("]);");
         println!();
 
+        for version in result.custom_query_versions().into_iter() {
+            println!(
+                "const {}: FieldElement = FieldElement::from_mont([",
+                felt_expr(version)
+            );
+            for limb in felt_to_mont(version).iter() {
+                println!("    {limb},");
+            }
+            println!("]);");
+            println!();
+        }
+
+        if result
+            .model_types
+            .iter()
+            .any(|item| matches!(item.content, RustTypeKind::PatternString(_)))
+        {
+            print_pattern_mismatch_error();
+            println!();
+        }
+
+        let mut bool_values = result
+            .model_types
+            .iter()
+            .chain(result.request_response_types.iter())
+            .filter_map(|item| match &item.content {
+                RustTypeKind::Enum(inner) => Some(inner),
+                _ => None,
+            })
+            .flat_map(|item| item.variants.iter())
+            .filter_map(|variant| variant.bool_value)
+            .collect::<Vec<_>>();
+        bool_values.sort();
+        bool_values.dedup();
+        for value in bool_values.into_iter() {
+            print_bool_deserializer(value);
+            println!();
+        }
+
         let mut manual_serde_types = vec![];
 
         for rust_type in result
@@ -223,6 +417,12 @@ impl Generate {
             println!();
         }
 
+        for fragment_trait in result.fragment_traits.iter() {
+            fragment_trait.render_stdout();
+
+            println!();
+        }
+
         for (ind, rust_type) in manual_serde_types.iter().enumerate() {
             rust_type.render_serde_stdout();
 
@@ -231,10 +431,114 @@ impl Generate {
             }
         }
 
+        result.render_tests_stdout(&specs, &profile.options);
+
         Ok(())
     }
 }
 
+impl TypeResolutionResult {
+    pub fn has_deprecations(&self) -> bool {
+        self.model_types
+            .iter()
+            .chain(self.request_response_types.iter())
+            .any(|item| {
+                item.deprecation.is_some()
+                    || matches!(
+                        &item.content,
+                        RustTypeKind::Struct(inner)
+                            if inner.fields.iter().any(|field| field.deprecation.is_some())
+                    )
+            })
+    }
+
+    /// Note for manually implemented types that must take a lifetime, in the list of them.
+    pub fn lifetime_note(&self, schema_name: &str) -> &'static str {
+        if self.borrowing_manual_types.contains(schema_name) {
+            " (generic over `'a` as it holds borrowing types)"
+        } else {
+            ""
+        }
+    }
+
+    /// Query-version values without named `FieldElement` constants, which get their own constants.
+    pub fn custom_query_versions(&self) -> Vec<[u64; 4]> {
+        let mut versions = vec![];
+        for rust_type in self
+            .model_types
+            .iter()
+            .chain(self.request_response_types.iter())
+        {
+            if let RustTypeKind::Struct(inner) = &rust_type.content {
+                for fixed in inner.fields.iter().filter_map(|field| field.fixed.as_ref()) {
+                    if let Some(version) = fixed
+                        .value
+                        .strip_prefix("&TRANSACTION_VERSION_")
+                        .and_then(|digits| parse_felt(digits).ok())
+                    {
+                        if !versions.contains(&version) {
+                            versions.push(version);
+                        }
+                    }
+                }
+            }
+        }
+
+        versions.sort_by(|lhs, rhs| lhs.iter().rev().cmp(rhs.iter().rev()));
+        versions
+    }
+}
+
+impl RustTrait {
+    pub fn render_stdout(&self) {
+        if let Some(title) = &self.title {
+            print_doc(title, 0);
+            println!("///");
+        }
+        print_doc(
+            &format!(
+                "Implemented by the types that the `{}` schema is flattened into.",
+                self.fragment
+            ),
+            0,
+        );
+        println!("pub trait {} {{", self.name);
+        for field in self.fields.iter() {
+            if let Some(doc) = &field.description {
+                print_doc(doc, 4);
+            }
+            println!(
+                "    fn {}(&self) -> {};",
+                escape_name(&field.name),
+                field.accessor_type()
+            );
+        }
+        println!("}}");
+
+        for (implementor, lifetime) in self.implementors.iter() {
+            println!();
+            if *lifetime {
+                println!("impl<'a> {} for {}<'a> {{", self.name, implementor);
+            } else {
+                println!("impl {} for {} {{", self.name, implementor);
+            }
+            for (ind, field) in self.fields.iter().enumerate() {
+                if ind != 0 {
+                    println!();
+                }
+                println!(
+                    "    fn {}(&self) -> {} {{",
+                    escape_name(&field.name),
+                    field.accessor_type()
+                );
+                println!("        {}", field.accessor_value());
+                println!("    }}");
+            }
+            println!("}}");
+        }
+    }
+}
+
 impl RustType {
     pub fn render_stdout(&self) {
         match (self.title.as_ref(), self.description.as_ref()) {
@@ -252,14 +556,21 @@ impl RustType {
             (None, None) => {}
         }
 
-        self.content.render_stdout(&self.name);
+        if let Some(deprecation) = &self.deprecation {
+            print_deprecated(deprecation, 0);
+        }
+
+        self.content
+            .render_stdout(&self.name, &self.extra_attributes);
     }
 
     pub fn render_serde_stdout(&self) {
         match &self.content {
             RustTypeKind::Struct(content) => content.render_serde_stdout(&self.name),
+            RustTypeKind::Enum(content) => content.render_serde_stdout(&self.name),
             RustTypeKind::Unit(content) => content.render_serde_stdout(&self.name),
-            _ => todo!("serde blocks only implemented for structs and unit"),
+            RustTypeKind::PatternString(content) => content.render_serde_stdout(&self.name),
+            _ => todo!("serde blocks only implemented for structs, enums and unit"),
         }
     }
 
@@ -269,23 +580,92 @@ impl RustType {
             RustTypeKind::Enum(content) => content.need_custom_serde(),
             RustTypeKind::Wrapper(content) => content.need_custom_serde(),
             RustTypeKind::Unit(content) => content.need_custom_serde(),
+            RustTypeKind::PatternString(content) => content.need_custom_serde(),
+            RustTypeKind::Constant(content) => content.need_custom_serde(),
+        }
+    }
+}
+
+impl ExtraAttributes {
+    /// Prints the `derive` attribute with the extra derives appended, followed by extra attributes.
+    pub fn print_derive(&self, derives: &[&str]) {
+        let mut derives = derives
+            .iter()
+            .map(|item| item.to_string())
+            .collect::<Vec<_>>();
+        for derive in self.derives.iter() {
+            if !derives.contains(derive) {
+                derives.push(derive.to_owned());
+            }
+        }
+
+        // Same layout as `rustfmt`
+        let line = format!("#[derive({})]", derives.join(", "));
+        let wrapped_line = format!("    {},", derives.join(", "));
+        if line.len() <= MAX_LINE_LENGTH {
+            println!("{line}");
+        } else if wrapped_line.len() <= MAX_LINE_LENGTH {
+            println!("#[derive(");
+            println!("{wrapped_line}");
+            println!(")]");
+        } else {
+            println!("#[derive(");
+            for derive in derives.iter() {
+                println!("    {derive},");
+            }
+            println!(")]");
+        }
+
+        for attribute in self.attributes.iter() {
+            println!("{attribute}");
         }
     }
 }
 
 impl RustTypeKind {
-    pub fn render_stdout(&self, name: &str) {
+    pub fn render_stdout(&self, name: &str, extra: &ExtraAttributes) {
         match self {
-            Self::Struct(value) => value.render_stdout(name),
-            Self::Enum(value) => value.render_stdout(name),
-            Self::Wrapper(value) => value.render_stdout(name),
-            Self::Unit(value) => value.render_stdout(name),
+            Self::Struct(value) => value.render_stdout(name, extra),
+            Self::Enum(value) => value.render_stdout(name, extra),
+            Self::Wrapper(value) => value.render_stdout(name, extra),
+            Self::Unit(value) => value.render_stdout(name, extra),
+            Self::PatternString(value) => value.render_stdout(name, extra),
+            // Constants can't have derives
+            Self::Constant(value) => value.render_stdout(name),
         }
     }
+
+    /// Names of the types contained by value, as opposed to behind pointers or collections.
+    pub fn value_references(&self) -> Vec<String> {
+        let type_names: Vec<&str> = match self {
+            Self::Struct(value) => value
+                .fields
+                .iter()
+                .filter(|field| {
+                    field.fixed.is_none() && !field.wrapper.is_some_and(|item| item.is_pointer())
+                })
+                .map(|field| field.type_name.as_str())
+                .collect(),
+            Self::Enum(value) => value
+                .variants
+                .iter()
+                .filter_map(|variant| variant.payload.as_ref())
+                .map(|payload| payload.type_name.as_str())
+                .collect(),
+            Self::Wrapper(value) => vec![value.type_name.as_str()],
+            _ => vec![],
+        };
+
+        type_names
+            .into_iter()
+            .filter_map(value_reference)
+            .map(|type_name| type_name.to_owned())
+            .collect()
+    }
 }
 
 impl RustStruct {
-    pub fn render_stdout(&self, name: &str) {
+    pub fn render_stdout(&self, name: &str, extra: &ExtraAttributes) {
         let mut fields = self.fields.clone();
         if fields.iter().any(|field| {
             field
@@ -302,36 +682,42 @@ impl RustStruct {
                 name: "is_query".into(),
                 optional: false,
                 fixed: None,
-                arc_wrap: false,
+                wrapper: None,
                 type_name: "bool".into(),
                 serde_rename: None,
                 serde_faltten: false,
                 serializer: None,
+                deprecation: None,
+                ref_type: None,
+                fragments: vec![],
+                pattern: None,
             });
         }
 
         let derive_serde = !self.need_custom_serde();
 
-        if derive_serde
-            && self
-                .fields
-                .iter()
-                .any(|item| matches!(item.serializer, Some(SerializerOverride::SerdeAs(_))))
-        {
+        if derive_serde && self.fields.iter().any(|item| item.uses_serde_as()) {
             println!("#[serde_as]");
         }
         if derive_serde {
-            println!("#[derive(Debug, Clone, Serialize, Deserialize)]");
-            println!("#[cfg_attr(feature = \"no_unknown_fields\", serde(deny_unknown_fields))]");
+            extra.print_derive(&["Debug", "Clone", "Serialize", "Deserialize"]);
+            if !self.has_additional_properties() {
+                println!(
+                    "#[cfg_attr(feature = \"no_unknown_fields\", serde(deny_unknown_fields))]"
+                );
+            }
         } else {
-            println!("#[derive(Debug, Clone)]");
+            extra.print_derive(&["Debug", "Clone"]);
         }
-        println!("pub struct {name} {{");
+        println!("pub struct {name}{} {{", lifetime_params(self.lifetime));
 
         for field in fields.iter().filter(|field| field.fixed.is_none()) {
             if let Some(doc) = &field.description {
                 print_doc(doc, 4);
             }
+            if let Some(deprecation) = &field.deprecation {
+                print_deprecated(deprecation, 4);
+            }
 
             for line in field.def_lines(4, derive_serde, false, false) {
                 println!("{line}")
@@ -341,19 +727,199 @@ impl RustStruct {
         println!("}}");
 
         if self.extra_ref_type {
+            let ref_fields = fields
+                .iter()
+                .filter(|field| field.fixed.is_none())
+                .collect::<Vec<_>>();
+
             println!();
 
             print_doc(&format!("Reference version of [{}].", name), 0);
-            println!("#[derive(Debug, Clone)]");
+            if derive_serde {
+                if ref_fields.iter().any(|item| {
+                    item.ref_type.is_none()
+                        && matches!(item.serializer, Some(SerializerOverride::SerdeAs(_)))
+                }) {
+                    println!("#[serde_as]");
+                }
+                println!("#[derive(Debug, Clone, Copy, Serialize)]");
+            } else {
+                println!("#[derive(Debug, Clone, Copy)]");
+            }
             println!("pub struct {name}Ref<'a> {{");
 
-            for field in fields.iter().filter(|field| field.fixed.is_none()) {
-                for line in field.def_lines(4, false, true, false) {
+            for field in ref_fields.iter() {
+                for line in field.ref_def_lines(4, derive_serde) {
                     println!("{line}")
                 }
             }
 
             println!("}}");
+            println!();
+
+            let owned_type = format!("{name}{}", lifetime_params(self.lifetime));
+            let line = format!("impl<'a> From<&'a {owned_type}> for {name}Ref<'a> {{");
+            if line.len() > MAX_LINE_LENGTH {
+                println!("impl<'a> From<&'a {owned_type}>");
+                println!("    for {name}Ref<'a>");
+                println!("{{");
+            } else {
+                println!("{line}");
+            }
+            println!("    fn from(value: &'a {owned_type}) -> Self {{");
+            print_struct_literal(
+                "Self",
+                &ref_fields
+                    .iter()
+                    .map(|field| {
+                        let field_name = escape_name(&field.name);
+                        match &field.ref_type {
+                            Some(_) if field.wrapper == Some(FieldWrapper::Cow) => {
+                                match field.option_inner_type() {
+                                    Some(_) => {
+                                        format!("{field_name}: value.{field_name}.as_deref()")
+                                    }
+                                    None => format!("{field_name}: &value.{field_name}"),
+                                }
+                            }
+                            Some(_) if field.option_inner_type().is_some() => {
+                                format!("{field_name}: value.{field_name}.as_ref().map(Into::into)")
+                            }
+                            Some(_) if field.wrapper.is_some() => {
+                                format!("{field_name}: (&*value.{field_name}).into()")
+                            }
+                            Some(_) => format!("{field_name}: (&value.{field_name}).into()"),
+                            None => format!("{field_name}: &value.{field_name}"),
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            println!("    }}");
+            println!("}}");
+        }
+
+        if self.builder {
+            println!();
+            render_builder_stdout(
+                name,
+                self.lifetime,
+                &fields
+                    .into_iter()
+                    .filter(|field| field.fixed.is_none())
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        for conversion in self.conversions.iter() {
+            println!();
+            self.render_conversion_stdout(name, conversion);
+        }
+    }
+
+    fn render_conversion_stdout(&self, name: &str, conversion: &RustConversion) {
+        let source_type = format!("{name}{}", lifetime_params(self.lifetime));
+        let target_type = format!(
+            "{}{}",
+            conversion.target,
+            lifetime_params(conversion.target_lifetime)
+        );
+        let impl_params = if self.lifetime || conversion.target_lifetime {
+            vec![String::from("'a")]
+        } else {
+            vec![]
+        };
+
+        let receiver = if conversion.method.is_some() {
+            "self"
+        } else {
+            "value"
+        };
+        let fields = conversion
+            .fields
+            .iter()
+            .map(|(field_name, value)| {
+                let field_name = escape_name(field_name);
+                match value {
+                    ConversionValue::Moved {
+                        wrapper,
+                        is_option,
+                        method,
+                    } => {
+                        let value = match method {
+                            Some(method) => format!("{receiver}.{field_name}.{method}"),
+                            None => format!("{receiver}.{field_name}"),
+                        };
+                        let value = match wrapper {
+                            Some(wrapper) => wrapper.wrap_value(&value),
+                            None => value,
+                        };
+                        if *is_option {
+                            format!("{field_name}: Some({value})")
+                        } else {
+                            format!("{field_name}: {value}")
+                        }
+                    }
+                    ConversionValue::Expression(value) => format!("{field_name}: {value}"),
+                    ConversionValue::Param => field_name.to_owned(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let query_doc = conversion.drops_query.then(|| {
+            format!(
+                "The `is_query` flag is not carried over: a query-only transaction converts into a \
+                [{}] all the same, so check the flag before converting.",
+                conversion.target
+            )
+        });
+
+        match &conversion.method {
+            Some(method) => {
+                print_impl_header(&impl_params, &source_type);
+                print_doc(
+                    &format!(
+                        "Converts into [{}], taking the fields missing from [{}].",
+                        conversion.target, name
+                    ),
+                    4,
+                );
+                if let Some(doc) = &query_doc {
+                    println!("    ///");
+                    print_doc(doc, 4);
+                }
+                print_fn_header(
+                    method,
+                    &std::iter::once(String::from("self"))
+                        .chain(conversion.params.iter().cloned())
+                        .collect::<Vec<_>>(),
+                    &target_type,
+                );
+                print_struct_literal(&conversion.target, &fields);
+                println!("    }}");
+                println!("}}");
+            }
+            None => {
+                if let Some(doc) = &query_doc {
+                    print_doc(doc, 0);
+                }
+                let impl_params = if impl_params.is_empty() {
+                    String::new()
+                } else {
+                    format!("<{}>", impl_params.join(", "))
+                };
+                let line = format!("impl{impl_params} From<{source_type}> for {target_type} {{");
+                if line.len() > MAX_LINE_LENGTH {
+                    println!("impl{impl_params} From<{source_type}>");
+                    println!("    for {target_type}");
+                    println!("{{");
+                } else {
+                    println!("{line}");
+                }
+                println!("    fn from(value: {source_type}) -> Self {{");
+                print_struct_literal("Self", &fields);
+                println!("    }}");
+                println!("}}");
+            }
         }
     }
 
@@ -367,6 +933,13 @@ impl RustStruct {
         self.serde_as_array || self.fields.iter().any(|field| field.fixed.is_some())
     }
 
+    /// Serde doesn't support `deny_unknown_fields` on structs with a flattened map.
+    fn has_additional_properties(&self) -> bool {
+        self.fields
+            .iter()
+            .any(|field| field.serde_faltten && field.name == ADDITIONAL_PROPERTIES_FIELD)
+    }
+
     fn render_impl_serialize_stdout(&self, name: &str) {
         if self.serde_as_array {
             self.render_impl_array_serialize_stdout(name);
@@ -376,7 +949,9 @@ impl RustStruct {
     }
 
     fn render_impl_deserialize_stdout(&self, name: &str) {
-        if self.serde_as_array {
+        if self.serde_as_array && self.lifetime {
+            self.render_impl_borrowed_array_deserialize_stdout(name);
+        } else if self.serde_as_array {
             self.render_impl_array_deserialize_stdout(name);
         } else {
             self.render_impl_tagged_deserialize_stdout(name);
@@ -395,9 +970,17 @@ impl RustStruct {
     fn render_impl_array_serialize_stdout_inner(&self, name: &str, is_ref_type: bool) {
         println!(
             "impl{} Serialize for {}{} {{",
-            if is_ref_type { "<'a>" } else { "" },
+            if is_ref_type || self.lifetime {
+                "<'a>"
+            } else {
+                ""
+            },
             name,
-            if is_ref_type { "Ref<'a>" } else { "" },
+            if is_ref_type {
+                "Ref<'a>"
+            } else {
+                lifetime_params(self.lifetime)
+            },
         );
         println!(
             "    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{"
@@ -411,7 +994,12 @@ impl RustStruct {
             println!("        #[derive(Serialize)]");
             println!("        #[serde(transparent)]");
             println!("        struct Field{}<'a> {{", ind_field);
-            for line in field.def_lines(12, true, true, false).iter() {
+            let lines = if is_ref_type {
+                field.ref_def_lines(12, true)
+            } else {
+                field.def_lines(12, true, true, false)
+            };
+            for line in lines.iter() {
                 println!("{line}");
             }
             println!("        }}");
@@ -424,22 +1012,23 @@ impl RustStruct {
         println!();
 
         for (ind_field, field) in self.fields.iter().enumerate() {
+            let field_name = escape_name(&field.name);
             if field.name.len() > 5 {
                 println!("        seq.serialize_element(&Field{} {{", ind_field);
                 println!(
                     "            {}: {}self.{},",
-                    field.name,
+                    field_name,
                     if is_ref_type { "" } else { "&" },
-                    field.name
+                    field_name
                 );
                 println!("        }})?;");
             } else {
                 println!(
                     "        seq.serialize_element(&Field{} {{ {}: {}self.{} }})?;",
                     ind_field,
-                    field.name,
+                    field_name,
                     if is_ref_type { "" } else { "&" },
-                    field.name
+                    field_name
                 );
             }
         }
@@ -452,7 +1041,29 @@ impl RustStruct {
     }
 
     fn render_impl_tagged_serialize_stdout(&self, name: &str) {
-        println!("impl Serialize for {name} {{");
+        self.render_impl_tagged_serialize_stdout_inner(name, false);
+
+        if self.extra_ref_type {
+            println!();
+            self.render_impl_tagged_serialize_stdout_inner(name, true);
+        }
+    }
+
+    fn render_impl_tagged_serialize_stdout_inner(&self, name: &str, is_ref_type: bool) {
+        println!(
+            "impl{} Serialize for {}{} {{",
+            if is_ref_type || self.lifetime {
+                "<'a>"
+            } else {
+                ""
+            },
+            name,
+            if is_ref_type {
+                "Ref<'a>"
+            } else {
+                lifetime_params(self.lifetime)
+            },
+        );
         println!(
             "    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{"
         );
@@ -469,7 +1080,12 @@ impl RustStruct {
         println!("        struct Tagged<'a> {{");
 
         for field in self.fields.iter() {
-            for line in field.def_lines(12, true, true, false).iter() {
+            let lines = if is_ref_type && field.fixed.is_none() {
+                field.ref_def_lines(12, true)
+            } else {
+                field.def_lines(12, true, true, false)
+            };
+            for line in lines.iter() {
                 println!("{line}");
             }
         }
@@ -480,8 +1096,9 @@ impl RustStruct {
         for field in self.fields.iter().filter_map(|field| field.fixed.as_ref()) {
             if field.is_query_version {
                 println!(
-                    "        let {} = &(if self.is_query {{",
-                    escape_name(&field.name)
+                    "        let {} = &(if {}self.is_query {{",
+                    escape_name(&field.name),
+                    if is_ref_type { "*" } else { "" }
                 );
                 println!(
                     "            {} + QUERY_VERSION_OFFSET",
@@ -509,8 +1126,9 @@ impl RustStruct {
                     println!("            {},", escape_name(&field.name))
                 }
                 None => println!(
-                    "            {}: &self.{},",
+                    "            {}: {}self.{},",
                     escape_name(&field.name),
+                    if is_ref_type { "" } else { "&" },
                     escape_name(&field.name)
                 ),
             }
@@ -524,7 +1142,100 @@ impl RustStruct {
         println!("}}");
     }
 
-    fn render_impl_array_deserialize_stdout(&self, name: &str) {
+    /// Going through `serde_json::Value` would lose borrowed data, so the array and object forms
+    /// are instead tried in turn with an untagged enum, which buffers input without copying it.
+    fn render_impl_borrowed_array_deserialize_stdout(&self, name: &str) {
+        println!("impl<'de: 'a, 'a> Deserialize<'de> for {name}<'a> {{");
+        println!("    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{");
+
+        println!("        #[serde_as]");
+        println!("        #[derive(Deserialize)]");
+        println!("        struct AsObject<'a> {{");
+
+        for field in self.fields.iter() {
+            for line in field.def_lines(12, true, false, false).iter() {
+                println!("{line}");
+            }
+        }
+
+        println!("        }}");
+        println!();
+
+        for (ind_field, field) in self.fields.iter().enumerate() {
+            if field.serializer.is_some() || field.uses_serde_as() {
+                println!("        #[serde_as]");
+            }
+
+            println!("        #[derive(Deserialize)]");
+            println!("        #[serde(transparent)]");
+            println!(
+                "        struct Field{}{} {{",
+                ind_field,
+                lifetime_params(field.borrows())
+            );
+            for line in field.def_lines(12, true, false, false).iter() {
+                println!("{line}");
+            }
+            println!("        }}");
+            println!();
+        }
+
+        println!("        #[derive(Deserialize)]");
+        println!("        #[serde(untagged)]");
+        println!("        enum Params<'a> {{");
+        let elements = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(ind_field, field)| {
+                if field.borrows() {
+                    format!("#[serde(borrow)] Field{ind_field}<'a>")
+                } else {
+                    format!("Field{ind_field}")
+                }
+            })
+            .collect::<Vec<_>>();
+        // Same width limit as `rustfmt` applies to arguments
+        if elements.join(", ").len() <= 60 {
+            println!("            Array({}),", elements.join(", "));
+        } else {
+            println!("            Array(");
+            for element in elements.iter() {
+                println!("                {element},");
+            }
+            println!("            ),");
+        }
+        println!("            #[serde(borrow)]");
+        println!("            Object(AsObject<'a>),");
+        println!("        }}");
+        println!();
+
+        let field_names = (0..self.fields.len())
+            .map(|ind_field| format!("field{ind_field}"))
+            .collect::<Vec<_>>();
+        println!("        match Params::deserialize(deserializer)? {{");
+        println!(
+            "            Params::Array({}) => Ok(Self {{",
+            field_names.join(", ")
+        );
+        for (ind_field, field) in self.fields.iter().enumerate() {
+            let field_name = escape_name(&field.name);
+            println!("                {field_name}: field{ind_field}.{field_name},");
+        }
+        println!("            }}),");
+        println!("            Params::Object(object) => Ok(Self {{");
+        for field in self.fields.iter() {
+            let field_name = escape_name(&field.name);
+            println!("                {field_name}: object.{field_name},");
+        }
+        println!("            }}),");
+        println!("        }}");
+
+        println!("    }}");
+        println!("}}");
+    }
+
+    fn render_impl_array_deserialize_stdout(&self, name: &str) {
         println!("impl<'de> Deserialize<'de> for {name} {{");
         println!("    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{");
 
@@ -542,7 +1253,7 @@ impl RustStruct {
         println!();
 
         for (ind_field, field) in self.fields.iter().enumerate() {
-            if field.serializer.is_some() {
+            if field.serializer.is_some() || field.uses_serde_as() {
                 println!("        #[serde_as]");
             }
 
@@ -579,10 +1290,8 @@ impl RustStruct {
         println!("            Ok(Self {{");
 
         for (ind_field, field) in self.fields.iter().enumerate() {
-            println!(
-                "                {}: field{}.{},",
-                field.name, ind_field, field.name
-            );
+            let field_name = escape_name(&field.name);
+            println!("                {field_name}: field{ind_field}.{field_name},");
         }
 
         println!("            }})");
@@ -592,7 +1301,8 @@ impl RustStruct {
         println!("            Ok(Self {{");
 
         for field in self.fields.iter() {
-            println!("                {}: object.{},", field.name, field.name);
+            let field_name = escape_name(&field.name);
+            println!("                {field_name}: object.{field_name},");
         }
 
         println!("            }})");
@@ -606,7 +1316,11 @@ impl RustStruct {
     }
 
     fn render_impl_tagged_deserialize_stdout(&self, name: &str) {
-        println!("impl<'de> Deserialize<'de> for {name} {{");
+        if self.lifetime {
+            println!("impl<'de: 'a, 'a> Deserialize<'de> for {name}<'a> {{");
+        } else {
+            println!("impl<'de> Deserialize<'de> for {name} {{");
+        }
         println!("    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{");
 
         if self
@@ -618,10 +1332,12 @@ impl RustStruct {
         }
 
         println!("        #[derive(Deserialize)]");
-        println!(
-            "        #[cfg_attr(feature = \"no_unknown_fields\", serde(deny_unknown_fields))]"
-        );
-        println!("        struct Tagged {{");
+        if !self.has_additional_properties() {
+            println!(
+                "        #[cfg_attr(feature = \"no_unknown_fields\", serde(deny_unknown_fields))]"
+            );
+        }
+        println!("        struct Tagged{} {{", lifetime_params(self.lifetime));
 
         for field in self.fields.iter() {
             let lines = match &field.fixed {
@@ -630,7 +1346,7 @@ impl RustStruct {
                     name: field.name.clone(),
                     optional: false,
                     fixed: Some(fixed.to_owned()),
-                    arc_wrap: false,
+                    wrapper: None,
                     type_name: if fixed.must_present_in_deser {
                         field.type_name.to_owned()
                     } else {
@@ -639,6 +1355,10 @@ impl RustStruct {
                     serde_rename: field.serde_rename.clone(),
                     serde_faltten: field.serde_faltten,
                     serializer: field.serializer.as_ref().map(|value| value.to_optional()),
+                    deprecation: None,
+                    ref_type: None,
+                    fragments: vec![],
+                    pattern: None,
                 }
                 .def_lines(12, true, false, true),
                 None => field.def_lines(12, true, false, true),
@@ -654,24 +1374,33 @@ impl RustStruct {
         println!("        let tagged = Tagged::deserialize(deserializer)?;");
         println!();
 
-        for fixed_field in self.fields.iter().filter_map(|field| field.fixed.as_ref()) {
+        for (field, fixed_field) in self
+            .fields
+            .iter()
+            .filter_map(|field| field.fixed.as_ref().map(|fixed| (field, fixed)))
+        {
+            // Errors name the field as it's written in JSON
+            let json_name = field.serde_rename.as_ref().unwrap_or(&field.name);
+
             if fixed_field.is_query_version {
                 println!(
                     "        let is_query = if tagged.{} == {} {{",
-                    fixed_field.name,
+                    escape_name(&fixed_field.name),
                     fixed_field.value.trim_start_matches('&')
                 );
                 println!("            false");
                 println!(
                     "        }} else if tagged.{} == {} + QUERY_VERSION_OFFSET {{",
-                    fixed_field.name,
+                    escape_name(&fixed_field.name),
                     fixed_field.value.trim_start_matches('&')
                 );
                 println!("            true");
                 println!("        }} else {{");
-                println!(
-                    "            return Err(serde::de::Error::custom(\"invalid `{}` value\"));",
-                    fixed_field.name
+                print_fixed_field_mismatch(
+                    fixed_field,
+                    json_name,
+                    &format!("tagged.{}", escape_name(&fixed_field.name)),
+                    12,
                 );
                 println!("        }};");
                 println!();
@@ -688,9 +1417,11 @@ impl RustStruct {
                         &fixed_field.value
                     }
                 );
-                println!(
-                    "            return Err(serde::de::Error::custom(\"invalid `{}` value\"));",
-                    fixed_field.name
+                print_fixed_field_mismatch(
+                    fixed_field,
+                    json_name,
+                    &format!("tagged.{}", escape_name(&fixed_field.name)),
+                    12,
                 );
                 println!("        }}");
                 println!();
@@ -700,10 +1431,7 @@ impl RustStruct {
                     escape_name(&fixed_field.name)
                 );
                 println!("            if tag_field != {} {{", fixed_field.value);
-                println!(
-                    "                return Err(serde::de::Error::custom(\"invalid `{}` value\"));",
-                    fixed_field.name
-                );
+                print_fixed_field_mismatch(fixed_field, json_name, "tag_field", 16);
                 println!("            }}");
                 println!("        }}");
                 println!();
@@ -716,10 +1444,10 @@ impl RustStruct {
             println!(
                 "            {}: {},",
                 escape_name(&field.name),
-                if field.arc_wrap {
-                    format!("OwnedPtr::new(tagged.{})", escape_name(&field.name))
-                } else {
-                    format!("tagged.{}", escape_name(&field.name))
+                match field.wrapper {
+                    Some(wrapper) if wrapper.is_pointer() =>
+                        wrapper.wrap_value(&format!("tagged.{}", escape_name(&field.name))),
+                    _ => format!("tagged.{}", escape_name(&field.name)),
                 }
             );
         }
@@ -741,9 +1469,37 @@ impl RustStruct {
 }
 
 impl RustEnum {
-    pub fn render_stdout(&self, name: &str) {
-        println!("#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]");
-        println!("pub enum {name} {{");
+    pub fn render_stdout(&self, name: &str, extra: &ExtraAttributes) {
+        if self.is_untagged() {
+            if self.variants.iter().any(|variant| {
+                matches!(
+                    variant.payload,
+                    Some(RustFieldType {
+                        serializer: Some(_),
+                        ..
+                    })
+                )
+            }) {
+                println!("#[serde_as]");
+            }
+            extra.print_derive(&["Debug", "Clone", "Serialize", "Deserialize"]);
+            println!("#[serde(untagged)]");
+        } else if self.unknown_variant {
+            // Serde impls are generated as unknown values can't be captured with derives
+            extra.print_derive(&["Debug", "Clone", "PartialEq", "Eq"]);
+            println!("#[non_exhaustive]");
+        } else {
+            extra.print_derive(&[
+                "Debug",
+                "Clone",
+                "Copy",
+                "PartialEq",
+                "Eq",
+                "Serialize",
+                "Deserialize",
+            ]);
+        }
+        println!("pub enum {name}{} {{", lifetime_params(self.lifetime));
 
         for variant in self.variants.iter() {
             if let Some(doc) = &variant.description {
@@ -751,9 +1507,39 @@ impl RustEnum {
             }
 
             if let Some(rename) = &variant.serde_name {
-                println!("    #[serde(rename = \"{rename}\")]");
+                if !self.unknown_variant {
+                    println!("    #[serde(rename = \"{rename}\")]");
+                }
+            }
+            match &variant.payload {
+                Some(payload) => println!(
+                    "    {}({}{}{}{}),",
+                    variant.name,
+                    if has_lifetime(&payload.type_name) {
+                        "#[serde(borrow)] "
+                    } else {
+                        ""
+                    },
+                    variant
+                        .bool_value
+                        .map(|value| format!(
+                            "#[serde(deserialize_with = \"deserialize_{value}\")] "
+                        ))
+                        .unwrap_or_default(),
+                    payload
+                        .serializer
+                        .as_ref()
+                        .map(|serializer| format!("{} ", serializer.to_attribute()))
+                        .unwrap_or_default(),
+                    payload.type_name
+                ),
+                None => println!("    {},", variant.name),
             }
-            println!("    {},", variant.name);
+        }
+
+        if self.unknown_variant {
+            print_doc("A value not known when the types were generated.", 4);
+            println!("    Unknown(String),");
         }
 
         println!("}}");
@@ -781,15 +1567,88 @@ impl RustEnum {
         }
     }
 
+    pub fn render_serde_stdout(&self, name: &str) {
+        println!("impl Serialize for {name} {{");
+        println!(
+            "    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{"
+        );
+        println!("        serializer.serialize_str(match self {{");
+        for variant in self.variants.iter() {
+            println!(
+                "            Self::{} => {:?},",
+                variant.name,
+                variant.serde_name.as_ref().unwrap_or(&variant.name)
+            );
+        }
+        println!("            Self::Unknown(value) => value.as_str(),");
+        println!("        }})");
+        println!("    }}");
+        println!("}}");
+        println!();
+        println!("impl<'de> Deserialize<'de> for {name} {{");
+        println!("    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{");
+        println!("        let value = String::deserialize(deserializer)?;");
+        println!("        Ok(match value.as_str() {{");
+        for variant in self.variants.iter() {
+            println!(
+                "            {:?} => Self::{},",
+                variant.serde_name.as_ref().unwrap_or(&variant.name),
+                variant.name
+            );
+        }
+        println!("            _ => Self::Unknown(value),");
+        println!("        }})");
+        println!("    }}");
+        println!("}}");
+    }
+
     pub fn need_custom_serde(&self) -> bool {
-        false
+        self.unknown_variant
+    }
+
+    /// Enums with data-carrying variants are matched by shape instead of by name.
+    fn is_untagged(&self) -> bool {
+        self.variants
+            .iter()
+            .any(|variant| variant.payload.is_some())
     }
 }
 
 impl RustWrapper {
-    pub fn render_stdout(&self, name: &str) {
-        println!("#[derive(Debug, Clone, Serialize, Deserialize)]");
-        println!("pub struct {}(pub {});", name, self.type_name);
+    pub fn render_stdout(&self, name: &str, extra: &ExtraAttributes) {
+        if matches!(self.serializer, Some(SerializerOverride::SerdeAs(_))) {
+            println!("#[serde_as]");
+        }
+        extra.print_derive(&["Debug", "Clone", "Serialize", "Deserialize"]);
+
+        let mut attributes = vec![];
+        if self.lifetime {
+            attributes.push(String::from("#[serde(borrow)]"));
+        }
+        if let Some(serializer) = &self.serializer {
+            attributes.push(serializer.to_attribute());
+        }
+
+        let name = format!("{name}{}", lifetime_params(self.lifetime));
+        let line = format!(
+            "pub struct {}({}pub {});",
+            name,
+            attributes
+                .iter()
+                .map(|attribute| format!("{attribute} "))
+                .collect::<String>(),
+            self.type_name
+        );
+        if line.len() <= MAX_LINE_LENGTH {
+            println!("{line}");
+        } else {
+            println!("pub struct {name}(");
+            for attribute in attributes.iter() {
+                println!("    {attribute}");
+            }
+            println!("    pub {},", self.type_name);
+            println!(");");
+        }
     }
 
     pub fn need_custom_serde(&self) -> bool {
@@ -798,11 +1657,11 @@ impl RustWrapper {
 }
 
 impl RustUnit {
-    pub fn render_stdout(&self, name: &str) {
+    pub fn render_stdout(&self, name: &str, extra: &ExtraAttributes) {
         if self.need_custom_serde() {
-            println!("#[derive(Debug, Clone)]");
+            extra.print_derive(&["Debug", "Clone"]);
         } else {
-            println!("#[derive(Debug, Clone, Serialize, Deserialize)]");
+            extra.print_derive(&["Debug", "Clone", "Serialize", "Deserialize"]);
         }
         println!("pub struct {};", name);
     }
@@ -847,13 +1706,159 @@ impl RustUnit {
     }
 }
 
+impl RustPatternString {
+    pub fn render_stdout(&self, name: &str, extra: &ExtraAttributes) {
+        extra.print_derive(&["Debug", "Clone", "PartialEq", "Eq", "Hash"]);
+        println!("pub struct {name}(String);");
+        println!();
+        println!("impl {name} {{");
+        println!("    pub const PATTERN: &str = {:?};", self.pattern);
+        println!();
+        println!("    pub fn as_str(&self) -> &str {{");
+        println!("        &self.0");
+        println!("    }}");
+        println!();
+        print_doc(
+            "Whether `value` matches [Self::PATTERN], using an automaton built from the pattern \
+            when generating the code.",
+            4,
+        );
+        println!("    fn is_match(value: &str) -> bool {{");
+        self.dfa.render_stdout(8);
+        println!("    }}");
+        println!("}}");
+        println!();
+        println!("impl core::str::FromStr for {name} {{");
+        println!("    type Err = PatternMismatchError;");
+        println!();
+        println!("    fn from_str(s: &str) -> Result<Self, Self::Err> {{");
+        println!("        if Self::is_match(s) {{");
+        println!("            Ok(Self(s.into()))");
+        println!("        }} else {{");
+        println!("            Err(PatternMismatchError {{");
+        println!("                type_name: \"{name}\",");
+        println!("                pattern: Self::PATTERN,");
+        println!("            }})");
+        println!("        }}");
+        println!("    }}");
+        println!("}}");
+        println!();
+        println!("impl core::fmt::Display for {name} {{");
+        println!("    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{");
+        println!("        write!(f, \"{{}}\", self.0)");
+        println!("    }}");
+        println!("}}");
+    }
+
+    pub fn render_serde_stdout(&self, name: &str) {
+        println!("impl Serialize for {name} {{");
+        println!(
+            "    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{"
+        );
+        println!("        serializer.serialize_str(&self.0)");
+        println!("    }}");
+        println!("}}");
+        println!();
+        println!("impl<'de> Deserialize<'de> for {name} {{");
+        println!("    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{");
+        println!("        let value = String::deserialize(deserializer)?;");
+        println!("        value.parse().map_err(serde::de::Error::custom)");
+        println!("    }}");
+        println!("}}");
+    }
+
+    pub fn need_custom_serde(&self) -> bool {
+        true
+    }
+}
+
+impl RustConstant {
+    pub fn render_stdout(&self, name: &str) {
+        println!(
+            "pub const {}: {} = {};",
+            camel_to_snake_case(name)
+                .trim_start_matches('_')
+                .to_ascii_uppercase(),
+            self.type_name,
+            self.value
+        );
+    }
+
+    pub fn need_custom_serde(&self) -> bool {
+        false
+    }
+}
+
 impl RustField {
+    /// Whether the field borrows from the deserializer input, through `Cow` or types with lifetimes.
+    pub fn borrows(&self) -> bool {
+        self.wrapper == Some(FieldWrapper::Cow) || has_lifetime(&self.type_name)
+    }
+
+    /// Return type of a getter borrowing the field, looking through smart pointers.
+    pub fn accessor_type(&self) -> String {
+        if self.type_name == "String" {
+            String::from("&str")
+        } else if self.type_name == "Option<String>" {
+            String::from("Option<&str>")
+        } else if let Some(item_type) = self
+            .type_name
+            .strip_prefix("Vec<")
+            .and_then(|value| value.strip_suffix('>'))
+        {
+            format!("&[{item_type}]")
+        } else {
+            format!("&{}", self.type_name)
+        }
+    }
+
+    /// Expression borrowing the field as [Self::accessor_type], working for `Cow` fields too.
+    pub fn accessor_value(&self) -> String {
+        if self.type_name == "Option<String>" {
+            format!("self.{}.as_deref()", escape_name(&self.name))
+        } else {
+            format!("&self.{}", escape_name(&self.name))
+        }
+    }
+
+    /// Whether the field needs `#[serde_as]` on its struct when wrappers are kept, which is also
+    /// the case for reference-counted pointers as serde needs its `rc` feature otherwise.
+    pub fn uses_serde_as(&self) -> bool {
+        match &self.serializer {
+            Some(serializer) => matches!(serializer, SerializerOverride::SerdeAs(_)),
+            None => self.wrapper.is_some_and(|wrapper| wrapper.is_shared()),
+        }
+    }
+
+    /// The wrapped type if the field is an `Option`.
+    pub fn option_inner_type(&self) -> Option<&str> {
+        self.type_name
+            .strip_prefix("Option<")
+            .and_then(|value| value.strip_suffix('>'))
+    }
+
+    /// Same as `def_lines` with `is_ref`, except that nested `Ref` types are used by value.
+    pub fn ref_def_lines(&self, leading_spaces: usize, serde_attrs: bool) -> Vec<String> {
+        let mut lines = self.def_lines(leading_spaces, serde_attrs, true, false);
+
+        if let (Some(ref_type), Some(last_line)) = (&self.ref_type, lines.last_mut()) {
+            *last_line = format!(
+                "{}pub {}: {},",
+                " ".repeat(leading_spaces),
+                escape_name(&self.name),
+                ref_type
+            );
+        }
+
+        lines
+    }
+
     pub fn def_lines(
         &self,
         leading_spaces: usize,
         serde_attrs: bool,
         is_ref: bool,
-        no_arc_wrapping: bool,
+        no_wrapping: bool,
     ) -> Vec<String> {
         let mut lines = vec![];
 
@@ -873,7 +1878,15 @@ impl RustField {
             if self.serde_faltten {
                 lines.push(format!("{leading_spaces}#[serde(flatten)]"));
             }
-            if let Some(serde_as) = &self.serializer {
+            if !is_ref && self.borrows() {
+                lines.push(format!("{leading_spaces}#[serde(borrow)]"));
+            }
+            // `Cow` is serialized as is
+            if let Some(serde_as) = self
+                .serializer
+                .as_ref()
+                .filter(|_| !is_ref || self.wrapper != Some(FieldWrapper::Cow))
+            {
                 lines.push(match serde_as {
                     SerializerOverride::Serde(serializer) => {
                         format!("{leading_spaces}#[serde(with = \"{serializer}\")]")
@@ -891,12 +1904,27 @@ impl RustField {
                             }
                         } else if is_ref && serializer.starts_with("Vec<") {
                             format!("[{}]", &serializer[4..(serializer.len() - 1)])
+                        } else if let (Some(wrapper), false) = (
+                            // Serializers of `Cow` fields already cover the wrapper
+                            self.wrapper
+                                .filter(|wrapper| !no_wrapping && wrapper.is_pointer()),
+                            is_ref,
+                        ) {
+                            wrapper.wrap_type(serializer)
                         } else {
                             serializer.to_owned()
                         };
                         format!("{leading_spaces}#[serde_as(as = \"{serializer}\")]")
                     }
                 });
+            } else if let Some(wrapper) = self
+                .wrapper
+                .filter(|wrapper| !is_ref && !no_wrapping && wrapper.is_shared())
+            {
+                lines.push(format!(
+                    "{leading_spaces}#[serde_as(as = \"{}\")]",
+                    wrapper.wrap_type("serde_with::Same")
+                ));
             }
         }
 
@@ -921,13 +1949,18 @@ impl RustField {
             if is_ref {
                 if type_name == "String" {
                     String::from("&'a str")
+                } else if self.wrapper == Some(FieldWrapper::Cow) {
+                    format!("&'a {}", FieldWrapper::Cow.wrap_type(type_name))
                 } else if type_name.starts_with("Vec<") {
                     format!("&'a [{}]", &type_name[4..(type_name.len() - 1)])
                 } else {
                     format!("&'a {}", type_name)
                 }
-            } else if self.arc_wrap && !no_arc_wrapping {
-                format!("OwnedPtr<{}>", type_name)
+            } else if let Some(wrapper) = self
+                .wrapper
+                .filter(|wrapper| !no_wrapping || !wrapper.is_pointer())
+            {
+                wrapper.wrap_type(type_name)
             } else {
                 type_name.to_owned()
             },
@@ -937,7 +1970,52 @@ impl RustField {
     }
 }
 
+impl FieldWrapper {
+    /// Smart pointers, as opposed to `Cow`, which is kept as-is in serde helper types.
+    pub fn is_pointer(&self) -> bool {
+        !matches!(self, Self::Cow)
+    }
+
+    /// Reference-counted pointers, `OwnedPtr` included as it may be an `Arc`.
+    pub fn is_shared(&self) -> bool {
+        matches!(self, Self::Arc | Self::Rc | Self::OwnedPtr)
+    }
+
+    pub fn wrap_type(&self, type_name: &str) -> String {
+        match self {
+            Self::OwnedPtr => format!("OwnedPtr<{type_name}>"),
+            Self::Arc => format!("alloc::sync::Arc<{type_name}>"),
+            Self::Box => format!("alloc::boxed::Box<{type_name}>"),
+            Self::Rc => format!("alloc::rc::Rc<{type_name}>"),
+            // Only ever applied to `String` and `Option<String>`
+            Self::Cow => match generic_argument(type_name, "Option") {
+                Some(_) => String::from("Option<alloc::borrow::Cow<'a, str>>"),
+                None => String::from("alloc::borrow::Cow<'a, str>"),
+            },
+        }
+    }
+
+    pub fn wrap_value(&self, value: &str) -> String {
+        match self {
+            Self::OwnedPtr => format!("OwnedPtr::new({value})"),
+            Self::Arc => format!("alloc::sync::Arc::new({value})"),
+            Self::Box => format!("alloc::boxed::Box::new({value})"),
+            Self::Rc => format!("alloc::rc::Rc::new({value})"),
+            Self::Cow => format!("alloc::borrow::Cow::Owned({value})"),
+        }
+    }
+}
+
 impl SerializerOverride {
+    pub fn to_attribute(&self) -> String {
+        match self {
+            SerializerOverride::Serde(serializer) => format!("#[serde(with = \"{serializer}\")]"),
+            SerializerOverride::SerdeAs(serializer) => {
+                format!("#[serde_as(as = \"{serializer}\")]")
+            }
+        }
+    }
+
     pub fn to_optional(&self) -> Self {
         match self {
             SerializerOverride::Serde(_) => {
@@ -948,21 +2026,108 @@ impl SerializerOverride {
     }
 }
 
-fn resolve_types(
-    specs: &Specification,
-    flatten_option: &FlattenOption,
-    ignore_types: &[String],
-    fixed_fields: &FixedFieldsOptions,
-    arc_wrapping: &ArcWrappingOptions,
-) -> Result<TypeResolutionResult> {
+impl<'a> ResolutionContext<'a> {
+    pub fn new(specs: &Specification, options: &'a ProfileOptions) -> Self {
+        Self {
+            options,
+            flatten_option: resolve_flatten_option(specs, &options.flatten_options),
+            hoisted_schemas: IndexMap::new(),
+            hoisted_types: vec![],
+            flattening: vec![],
+        }
+    }
+
+    /// Built-in type mapping for a schema, unless it's configured to be generated as a newtype.
+    pub fn field_type_override(&self, type_name: &str) -> Option<RustFieldType> {
+        if self
+            .options
+            .pattern_newtypes
+            .iter()
+            .any(|item| item == type_name)
+        {
+            None
+        } else {
+            get_field_type_override(type_name)
+        }
+    }
+
+    /// Deprecation note for an item marked with the `deprecated` keyword, or whose description
+    /// says it's deprecated when inference is enabled in the profile.
+    pub fn spec_deprecation(
+        &self,
+        deprecated: bool,
+        description: Option<&String>,
+    ) -> Option<String> {
+        let from_description = description.and_then(|value| find_deprecation_sentence(value));
+
+        if deprecated {
+            Some(
+                from_description.unwrap_or_else(|| String::from("Deprecated by the specification")),
+            )
+        } else if self.options.deprecations.infer_from_descriptions {
+            from_description
+        } else {
+            None
+        }
+    }
+
+    /// Name of the type synthesized for an anonymous schema used by a field.
+    pub fn type_name(&self, parent_name: &str, field_name: &str) -> String {
+        self.options.hoisting.resolve_name(&format!(
+            "{}{}",
+            parent_name,
+            to_pascal_case(&to_rust_field_name(field_name))
+        ))
+    }
+
+    /// Registers a synthesized type. The same schema can be hoisted more than once (e.g. when it's
+    /// part of a flattened fragment), but different schemas can't share a name.
+    pub fn hoist(
+        &mut self,
+        schema: &Schema,
+        name: &str,
+        content: impl FnOnce(&mut Self) -> Result<RustTypeKind>,
+    ) -> Result<()> {
+        let schema_value = serde_json::to_value(schema)?;
+        match self.hoisted_schemas.get(name) {
+            Some(existing) if existing == &schema_value => return Ok(()),
+            Some(_) => anyhow::bail!("Conflicting schemas hoisted into the same type name: {name}"),
+            None => {}
+        }
+        self.hoisted_schemas.insert(name.to_owned(), schema_value);
+
+        let deprecation =
+            self.spec_deprecation(schema.annotations().deprecated, schema.description());
+        let content = content(self)?;
+        self.hoisted_types.push(RustType {
+            title: schema.title().map(|value| to_starknet_rs_doc(value, true)),
+            description: schema
+                .description()
+                .or_else(|| schema.summary())
+                .map(|value| to_starknet_rs_doc(value, true)),
+            name: name.to_owned(),
+            deprecation,
+            extra_attributes: ExtraAttributes::default(),
+            content,
+        });
+
+        Ok(())
+    }
+}
+
+fn resolve_types(specs: &Specification, options: &ProfileOptions) -> Result<TypeResolutionResult> {
     let mut types = vec![];
     let mut req_types: Vec<RustType> = vec![];
     let mut not_implemented_types = vec![];
+    let mut context = ResolutionContext::new(specs, options);
+
+    let flatten_only_types = get_flatten_only_schemas(specs, &context.flatten_option);
 
-    let flatten_only_types = get_flatten_only_schemas(specs, flatten_option);
+    // Schemas each generated type name comes from, to catch renames clashing with each other
+    let mut schema_names: HashMap<String, &String> = HashMap::new();
 
     for (name, entity) in specs.components.schemas.iter() {
-        let rusty_name = to_starknet_rs_name(name);
+        let rusty_name = to_type_name(name, &options.naming);
 
         let title = entity.title();
         let description = match entity.description() {
@@ -971,12 +2136,12 @@ fn resolve_types(
         };
 
         // Explicitly ignored types
-        if ignore_types.contains(name) {
+        if options.ignore_types.contains(name) {
             continue;
         }
 
         // Manual override exists
-        if get_field_type_override(name).is_some() {
+        if context.field_type_override(name).is_some() {
             continue;
         }
 
@@ -984,9 +2149,14 @@ fn resolve_types(
             continue;
         }
 
-        let mut content = match schema_to_rust_type_kind(specs, entity, flatten_option)? {
-            Some(content) => content,
-            None => {
+        if let Some(existing) = schema_names.insert(rusty_name.clone(), name) {
+            anyhow::bail!("Schemas {existing} and {name} both map to type name {rusty_name}");
+        }
+
+        let mut content = match schema_to_rust_type_kind(specs, entity, &mut context, &rusty_name)?
+        {
+            Some(content) => content,
+            None => {
                 not_implemented_types.push(name.to_owned());
 
                 eprintln!("OneOf enum generation not implemented. Enum not generated for {name}");
@@ -996,14 +2166,21 @@ fn resolve_types(
 
         if let RustTypeKind::Struct(inner) = &mut content {
             for field in inner.fields.iter_mut() {
-                field.fixed = fixed_fields.find_fixed_field(&rusty_name, &field.name);
-                field.arc_wrap = arc_wrapping.in_field_wrapped(&rusty_name, &field.name);
+                if let Some(fixed) = options
+                    .fixed_field_types
+                    .find_fixed_field(&rusty_name, &field.name)
+                {
+                    field.fixed = Some(fixed);
+                }
             }
         }
 
         types.push(RustType {
             title: title.map(|value| to_starknet_rs_doc(value, true)),
             description: description.map(|value| to_starknet_rs_doc(value, true)),
+            deprecation: context
+                .spec_deprecation(entity.annotations().deprecated, entity.description()),
+            extra_attributes: ExtraAttributes::default(),
             name: rusty_name,
             content,
         });
@@ -1013,8 +2190,12 @@ fn resolve_types(
         title: Some(String::from("JSON-RPC error codes")),
         description: None,
         name: String::from("StarknetError"),
+        deprecation: None,
+        extra_attributes: ExtraAttributes::default(),
         content: RustTypeKind::Enum(RustEnum {
             is_error: true,
+            lifetime: false,
+            unknown_variant: false,
             variants: specs
                 .components
                 .errors
@@ -1022,9 +2203,11 @@ fn resolve_types(
                 .map(|(name, err)| match err {
                     ErrorType::Error(err) => RustVariant {
                         description: Some(err.message.clone()),
-                        name: to_starknet_rs_name(name),
+                        name: to_variant_name("StarknetError", name, &options.naming),
                         serde_name: None,
                         error_text: Some(err.message.clone()),
+                        payload: None,
+                        bool_value: None,
                     },
                     ErrorType::Reference(_) => todo!("Error redirection not implemented"),
                 })
@@ -1034,33 +2217,60 @@ fn resolve_types(
 
     // Request/response types
     for method in specs.methods.iter() {
+        let request_name = method_type_name(&method.name, "Request");
+        let response_name = method_type_name(&method.name, "Response");
+
+        // Deprecating a method deprecates both its request and response types
+        let method_deprecation = options
+            .deprecations
+            .find_deprecation(&method.name)
+            .or_else(|| context.spec_deprecation(method.deprecated, method.description.as_ref()));
+
         let mut request_fields = vec![];
 
         for param in method.params.iter() {
-            let field_type = get_rust_type_for_field(&param.schema, specs)?;
+            let hoisted_name = context.type_name(&request_name, &param.name);
+            let field_type =
+                get_rust_type_for_field(&param.schema, specs, &mut context, &hoisted_name)?;
+
+            let field_name = options
+                .naming
+                .fields
+                .get(&format!("{}.{}", request_name, param.name))
+                .or_else(|| options.naming.fields.get(&param.name))
+                .unwrap_or(&param.name);
+            let field_name = to_identifier(field_name);
 
             request_fields.push(RustField {
                 description: param.description.clone(),
-                name: param.name.clone(),
+                name: field_name.clone(),
                 optional: !param.required,
                 fixed: None,
-                arc_wrap: false,
+                wrapper: None,
                 type_name: field_type.type_name,
-                serde_rename: None,
+                serde_rename: if field_name == param.name {
+                    None
+                } else {
+                    Some(param.name.clone())
+                },
                 serde_faltten: false,
                 serializer: field_type.serializer,
+                deprecation: context.spec_deprecation(
+                    param.deprecated || param.schema.annotations().deprecated,
+                    param.description.as_ref(),
+                ),
+                ref_type: None,
+                fragments: vec![],
+                pattern: None,
             });
         }
 
         let request_type = RustType {
             title: Some(format!("Request for method {}", method.name)),
             description: None,
-            name: format!(
-                "{}Request",
-                to_starknet_rs_name(&camel_to_snake_case(
-                    method.name.trim_start_matches("starknet_")
-                ))
-            ),
+            name: request_name,
+            deprecation: method_deprecation.clone(),
+            extra_attributes: ExtraAttributes::default(),
             content: if request_fields.is_empty() {
                 RustTypeKind::Unit(RustUnit {
                     serde_as_array: true,
@@ -1069,419 +2279,2457 @@ fn resolve_types(
                 RustTypeKind::Struct(RustStruct {
                     serde_as_array: true,
                     extra_ref_type: true,
+                    builder: false,
+                    lifetime: false,
                     fields: request_fields,
+                    conversions: vec![],
                 })
             },
         };
 
         req_types.push(request_type);
+
+        let response_type = RustType {
+            title: Some(format!("Response for method {}", method.name)),
+            description: method
+                .result
+                .description
+                .as_ref()
+                .map(|value| to_starknet_rs_doc(value, true)),
+            content: method_result_to_rust_type_kind(
+                specs,
+                &method.result.schema,
+                &mut context,
+                &response_name,
+            )?,
+            name: response_name,
+            deprecation: method_deprecation,
+            extra_attributes: ExtraAttributes::default(),
+        };
+
+        req_types.push(response_type);
+    }
+
+    for hoisted_type in context.hoisted_types.into_iter() {
+        if types
+            .iter()
+            .chain(req_types.iter())
+            .any(|item| item.name == hoisted_type.name)
+        {
+            anyhow::bail!(
+                "Hoisted type name conflicts with an existing type: {}",
+                hoisted_type.name
+            );
+        }
+
+        types.push(hoisted_type);
+    }
+
+    for rust_type in types.iter_mut().chain(req_types.iter_mut()) {
+        if let Some(deprecation) = options.deprecations.find_deprecation(&rust_type.name) {
+            rust_type.deprecation = Some(deprecation);
+        }
+
+        rust_type.extra_attributes = ExtraAttributes {
+            derives: options.extra_attributes.find_derives(&rust_type.name),
+            attributes: options.extra_attributes.find_attributes(&rust_type.name),
+        };
+        if let Some(derive) = rust_type
+            .extra_attributes
+            .derives
+            .iter()
+            .find(|derive| matches!(derive.as_str(), "Serialize" | "Deserialize"))
+        {
+            anyhow::bail!(
+                "Extra derive `{derive}` on {} conflicts with generated serde code",
+                rust_type.name
+            );
+        }
+
+        if options.unknown_variants.contains(&rust_type.name) {
+            match &mut rust_type.content {
+                RustTypeKind::Enum(inner) if !inner.is_error && !inner.is_untagged() => {
+                    inner.unknown_variant = true;
+                }
+                _ => anyhow::bail!(
+                    "Unknown variants are only supported for string enums: {}",
+                    rust_type.name
+                ),
+            }
+        }
+
+        if let RustTypeKind::Struct(inner) = &mut rust_type.content {
+            inner.builder = options.builders.contains(&rust_type.name);
+            if options.ref_types.contains(&rust_type.name) {
+                inner.extra_ref_type = true;
+            }
+
+            for field in inner.fields.iter_mut() {
+                if let Some(deprecation) = options
+                    .deprecations
+                    .find_deprecation(&format!("{}.{}", rust_type.name, field.name))
+                {
+                    field.deprecation = Some(deprecation);
+                }
+
+                if let Some(fixed) = field.fixed.as_mut().filter(|fixed| fixed.is_query_version) {
+                    resolve_query_version(fixed, &options.query_version)
+                        .with_context(|| format!("Invalid {}.{}", rust_type.name, field.name))?;
+
+                    // Query versions are always `FieldElement`s, even when the spec has strings
+                    if field.serializer.is_none() {
+                        field.serializer =
+                            Some(SerializerOverride::SerdeAs(String::from("UfeHex")));
+                    }
+                }
+
+                field.wrapper = options
+                    .field_wrapping
+                    .find_field_wrapper(&rust_type.name, &field.name);
+                if field.wrapper == Some(FieldWrapper::Cow) {
+                    match field.type_name.as_str() {
+                        "String" => {}
+                        // `Cow` only borrows when deserialized directly
+                        "Option<String>" => {
+                            field.serializer = Some(SerializerOverride::SerdeAs(String::from(
+                                "Option<serde_with::BorrowCow>",
+                            )));
+                        }
+                        _ => anyhow::bail!(
+                            "Only `String` and `Option<String>` fields can be wrapped in `Cow`: \
+                             {}.{}",
+                            rust_type.name,
+                            field.name
+                        ),
+                    }
+                }
+            }
+        }
+    }
+
+    check_listed_structs("builders", &options.builders, &types, &req_types)?;
+    check_wrapped_fields(&options.field_wrapping, &types, &req_types)?;
+    check_listed_structs("ref_types", &options.ref_types, &types, &req_types)?;
+    resolve_bool_variants(&options.bool_variants, &mut types, &mut req_types)?;
+
+    // Must happen before `Ref` types are assigned, as they'd be just as recursive
+    resolve_recursive_types(&mut types, &mut req_types);
+
+    // Fields of types with their own `Ref` types use them in other `Ref` types
+    let ref_type_names = types
+        .iter()
+        .chain(req_types.iter())
+        .filter(|item| matches!(&item.content, RustTypeKind::Struct(inner) if inner.extra_ref_type))
+        .map(|item| item.name.to_owned())
+        .collect::<HashSet<_>>();
+    for rust_type in types.iter_mut().chain(req_types.iter_mut()) {
+        if let RustTypeKind::Struct(inner) = &mut rust_type.content {
+            if !inner.extra_ref_type {
+                continue;
+            }
+
+            for field in inner.fields.iter_mut() {
+                if field.fixed.is_some() {
+                    continue;
+                }
+
+                field.ref_type = match field.option_inner_type() {
+                    // `Cow` fields are exposed as plain string slices
+                    _ if field.wrapper == Some(FieldWrapper::Cow) => {
+                        Some(String::from(match field.option_inner_type() {
+                            Some(_) => "Option<&'a str>",
+                            None => "&'a str",
+                        }))
+                    }
+                    Some(type_name)
+                        if field.wrapper.is_none() && ref_type_names.contains(type_name) =>
+                    {
+                        Some(format!("Option<{type_name}Ref<'a>>"))
+                    }
+                    None if ref_type_names.contains(&field.type_name) => {
+                        Some(format!("{}Ref<'a>", field.type_name))
+                    }
+                    _ => None,
+                };
+            }
+        }
     }
 
+    check_name_conflicts(&types, &req_types)?;
+    options.naming.check_unused_keys()?;
+
+    // Types left to be implemented manually borrow too if the types they hold do
+    let manual_schemas = options
+        .ignore_types
+        .iter()
+        .chain(not_implemented_types.iter())
+        .collect::<Vec<_>>();
+    let manual_types = manual_schemas
+        .iter()
+        .filter_map(|name| {
+            let references = specs.components.schemas.get(*name)?.references();
+            Some((
+                to_type_name(name, &options.naming),
+                references
+                    .into_iter()
+                    .map(|item| to_type_name(item, &options.naming))
+                    .collect(),
+            ))
+        })
+        .collect();
+    let lifetime_types = resolve_lifetimes(&mut types, &mut req_types, &manual_types)?;
+    let borrowing_manual_types = manual_schemas
+        .into_iter()
+        .filter(|name| lifetime_types.contains(&to_type_name(name, &options.naming)))
+        .cloned()
+        .collect();
+    resolve_conversions(&mut types, &mut req_types, &options.conversions)?;
+
     // Sorting the types makes it easier to check diffs in generated code.
     types.sort_by_key(|item| item.name.to_owned());
     req_types.sort_by_key(|item| item.name.to_owned());
     not_implemented_types.sort();
 
+    let fragment_traits = resolve_fragment_traits(specs, &types, &req_types, options)?;
+
     Ok(TypeResolutionResult {
         model_types: types,
         request_response_types: req_types,
+        fragment_traits,
         not_implemented: not_implemented_types,
+        borrowing_manual_types,
     })
 }
 
-fn schema_to_rust_type_kind(
-    specs: &Specification,
-    entity: &Schema,
-    flatten_option: &FlattenOption,
-) -> Result<Option<RustTypeKind>> {
-    Ok(match entity {
-        Schema::Ref(reference) => {
-            let mut fields = vec![];
-            let redirected_schema = specs
-                .components
-                .schemas
-                .get(reference.name())
-                .ok_or_else(|| anyhow::anyhow!(""))?;
-            get_schema_fields(redirected_schema, specs, &mut fields, flatten_option)?;
-            Some(RustTypeKind::Struct(RustStruct {
-                serde_as_array: false,
-                extra_ref_type: false,
-                fields,
-            }))
+/// Makes sure that the types listed in a profile option are generated structs, as typos would
+/// otherwise be silently ignored.
+fn check_listed_structs(
+    option: &str,
+    names: &[String],
+    types: &[RustType],
+    req_types: &[RustType],
+) -> Result<()> {
+    for name in names.iter() {
+        match types
+            .iter()
+            .chain(req_types.iter())
+            .find(|item| &item.name == name)
+        {
+            Some(RustType {
+                content: RustTypeKind::Struct(_),
+                ..
+            }) => {}
+            Some(_) => anyhow::bail!("Type listed in {option} is not a struct: {name}"),
+            None => anyhow::bail!("Type listed in {option} not found: {name}"),
         }
-        Schema::OneOf(_) => None,
-        Schema::AllOf(_) | Schema::Primitive(Primitive::Object(_)) => {
-            let mut fields = vec![];
-            get_schema_fields(entity, specs, &mut fields, flatten_option)?;
-            Some(RustTypeKind::Struct(RustStruct {
-                serde_as_array: false,
-                extra_ref_type: false,
-                fields,
-            }))
+    }
+
+    Ok(())
+}
+
+/// Sets the values accepted by `bool` variants listed in `bool_variants`.
+fn resolve_bool_variants(
+    bool_variants: &IndexMap<String, bool>,
+    types: &mut [RustType],
+    req_types: &mut [RustType],
+) -> Result<()> {
+    for (key, value) in bool_variants.iter() {
+        let (type_name, variant_name) = key
+            .split_once('.')
+            .ok_or_else(|| anyhow::anyhow!("Invalid key in bool_variants: {key}"))?;
+
+        let variant = types
+            .iter_mut()
+            .chain(req_types.iter_mut())
+            .find(|item| item.name == type_name)
+            .and_then(|item| match &mut item.content {
+                RustTypeKind::Enum(inner) => inner
+                    .variants
+                    .iter_mut()
+                    .find(|variant| variant.name == variant_name),
+                _ => None,
+            })
+            .ok_or_else(|| anyhow::anyhow!("Variant listed in bool_variants not found: {key}"))?;
+
+        if variant
+            .payload
+            .as_ref()
+            .is_none_or(|payload| payload.type_name != "bool")
+        {
+            anyhow::bail!("Variant listed in bool_variants doesn't hold a `bool`: {key}");
         }
-        Schema::Primitive(Primitive::String(value)) => match &value.r#enum {
-            Some(variants) => Some(RustTypeKind::Enum(RustEnum {
-                is_error: false,
-                variants: variants
-                    .iter()
-                    .map(|item| RustVariant {
-                        description: None,
-                        name: to_starknet_rs_name(item),
-                        serde_name: Some(item.to_owned()),
-                        error_text: None,
-                    })
-                    .collect(),
-            })),
-            None => {
-                anyhow::bail!("Unexpected non-enum string type when generating struct/enum");
-            }
-        },
-        _ => {
-            anyhow::bail!("Unexpected schema type when generating struct/enum");
+        if variant
+            .bool_value
+            .is_some_and(|spec_value| spec_value != *value)
+        {
+            anyhow::bail!("Value in bool_variants conflicts with the specification: {key}");
         }
-    })
-}
 
-/// Finds the list of schemas that are used and only used for flattening inside objects
-fn get_flatten_only_schemas(specs: &Specification, flatten_option: &FlattenOption) -> Vec<String> {
-    // We need this for now since we don't search method calls, so we could get false positives
-    const HARD_CODED_NON_FLATTEN_SCHEMAS: [&str; 2] = ["FUNCTION_CALL", "PENDING_STATE_UPDATE"];
+        variant.bool_value = Some(*value);
+    }
 
-    let mut flatten_fields = HashSet::<String>::new();
-    let mut non_flatten_fields = HashSet::<String>::new();
+    Ok(())
+}
 
-    for (_, schema) in specs.components.schemas.iter() {
-        visit_schema_for_flatten_only(
-            schema,
-            flatten_option,
-            &mut flatten_fields,
-            &mut non_flatten_fields,
-        );
-    }
+/// Makes sure that the fields listed in `field_wrapping` exist.
+fn check_wrapped_fields(
+    options: &FieldWrappingOptions,
+    types: &[RustType],
+    req_types: &[RustType],
+) -> Result<()> {
+    let names = options
+        .wrapped_types
+        .iter()
+        .map(|item| item.name.to_owned())
+        .collect::<Vec<_>>();
+    check_listed_structs("field_wrapping", &names, types, req_types)?;
+
+    for item in options.wrapped_types.iter() {
+        let rust_struct =
+            types
+                .iter()
+                .chain(req_types.iter())
+                .find_map(|rust_type| match &rust_type.content {
+                    RustTypeKind::Struct(inner) if rust_type.name == item.name => Some(inner),
+                    _ => None,
+                });
 
-    flatten_fields
-        .into_iter()
-        .filter_map(|item| {
-            if non_flatten_fields.contains(&item)
-                || HARD_CODED_NON_FLATTEN_SCHEMAS.contains(&item.as_str())
+        for field_name in item.fields.keys() {
+            if !rust_struct.is_some_and(|inner| inner.fields.iter().any(|f| &f.name == field_name))
             {
-                None
-            } else {
-                Some(item)
+                anyhow::bail!(
+                    "Field listed in field_wrapping not found: {}.{}",
+                    item.name,
+                    field_name
+                );
             }
-        })
-        .collect()
+        }
+    }
+
+    Ok(())
 }
 
-fn visit_schema_for_flatten_only(
-    schema: &Schema,
-    flatten_option: &FlattenOption,
-    flatten_fields: &mut HashSet<String>,
-    non_flatten_fields: &mut HashSet<String>,
-) {
-    match schema {
-        Schema::OneOf(one_of) => {
-            // Recursion
-            for variant in one_of.one_of.iter() {
-                match variant {
-                    Schema::Ref(reference) => {
-                        non_flatten_fields.insert(reference.name().to_owned());
-                    }
-                    _ => visit_schema_for_flatten_only(
-                        variant,
-                        flatten_option,
-                        flatten_fields,
-                        non_flatten_fields,
-                    ),
-                }
+fn check_name_conflicts(types: &[RustType], req_types: &[RustType]) -> Result<()> {
+    let mut type_names = HashSet::new();
+    for rust_type in types.iter().chain(req_types.iter()) {
+        // Types generated alongside structs count too
+        let extra_names = match &rust_type.content {
+            RustTypeKind::Struct(inner) => [
+                inner
+                    .extra_ref_type
+                    .then(|| format!("{}Ref", rust_type.name)),
+                inner.builder.then(|| format!("{}Builder", rust_type.name)),
+            ],
+            _ => [None, None],
+        };
+        for type_name in
+            std::iter::once(rust_type.name.to_owned()).chain(extra_names.into_iter().flatten())
+        {
+            if !type_names.insert(type_name.clone()) {
+                anyhow::bail!("Multiple types named {type_name}");
             }
         }
-        Schema::AllOf(all_of) => {
-            for fragment in all_of.all_of.iter() {
-                match fragment {
-                    Schema::Ref(reference) => {
-                        let should_flatten = match flatten_option {
-                            FlattenOption::All => true,
-                            FlattenOption::Selected(flatten_types) => {
-                                flatten_types.contains(&reference.name().to_owned())
-                            }
-                        };
 
-                        if should_flatten {
-                            flatten_fields.insert(reference.name().to_owned());
-                        } else {
-                            non_flatten_fields.insert(reference.name().to_owned());
-                            visit_schema_for_flatten_only(
-                                fragment,
-                                flatten_option,
-                                flatten_fields,
-                                non_flatten_fields,
-                            );
-                        }
+        let mut member_names = HashSet::new();
+        match &rust_type.content {
+            RustTypeKind::Struct(inner) => {
+                // Added when rendering query version fields
+                let is_query_name = String::from("is_query");
+                if inner.fields.iter().any(|field| {
+                    field
+                        .fixed
+                        .as_ref()
+                        .is_some_and(|fixed| fixed.is_query_version)
+                }) {
+                    member_names.insert(&is_query_name);
+                }
+
+                for field in inner.fields.iter() {
+                    if !member_names.insert(&field.name) {
+                        anyhow::bail!("Multiple fields named {} in {}", field.name, rust_type.name);
                     }
-                    _ => visit_schema_for_flatten_only(
-                        fragment,
-                        flatten_option,
-                        flatten_fields,
-                        non_flatten_fields,
-                    ),
                 }
             }
-        }
-        Schema::Primitive(Primitive::Object(object)) => {
-            for (_, prop_type) in object.properties.iter() {
-                match prop_type {
-                    Schema::Ref(reference) => {
-                        non_flatten_fields.insert(reference.name().to_owned());
+            RustTypeKind::Enum(inner) => {
+                let unknown_name = String::from("Unknown");
+                if inner.unknown_variant {
+                    member_names.insert(&unknown_name);
+                }
+
+                for variant in inner.variants.iter() {
+                    if !member_names.insert(&variant.name) {
+                        anyhow::bail!(
+                            "Multiple variants named {} in {}",
+                            variant.name,
+                            rust_type.name
+                        );
                     }
-                    _ => visit_schema_for_flatten_only(
-                        prop_type,
-                        flatten_option,
-                        flatten_fields,
-                        non_flatten_fields,
-                    ),
                 }
             }
+            _ => {}
         }
-        Schema::Primitive(Primitive::Array(array)) => match array.items.as_ref() {
-            Schema::Ref(reference) => {
-                non_flatten_fields.insert(reference.name().to_owned());
-            }
-            _ => visit_schema_for_flatten_only(
-                &array.items,
-                flatten_option,
-                flatten_fields,
-                non_flatten_fields,
-            ),
-        },
-        _ => {}
     }
-}
 
-fn get_schema_fields(
-    schema: &Schema,
-    specs: &Specification,
-    fields: &mut Vec<RustField>,
-    flatten_option: &FlattenOption,
-) -> Result<()> {
-    match schema {
-        Schema::Ref(value) => {
-            let ref_type_name = value.name();
-            let ref_type = match specs.components.schemas.get(ref_type_name) {
-                Some(ref_type) => ref_type,
-                None => anyhow::bail!("Ref target type not found: {}", ref_type_name),
-            };
+    Ok(())
+}
 
-            // Schema redirection
-            get_schema_fields(ref_type, specs, fields, flatten_option)?;
+/// Checks the version of a query-version fixed field against the profile, turning hex versions
+/// into `FieldElement` expressions.
+fn resolve_query_version(fixed: &mut FixedField, options: &QueryVersionOptions) -> Result<()> {
+    let value = fixed.value.trim_start_matches('&');
+    let version = match felt_from_expr(value) {
+        Some(version) => version,
+        None if value.starts_with("0x") => parse_felt(value)?,
+        None if options.valid_versions.is_empty() => return Ok(()),
+        None => anyhow::bail!("Unable to check query version against valid versions: {value}"),
+    };
+
+    if !options.valid_versions.is_empty() {
+        let mut valid_versions = options.valid_versions.iter().map(|item| parse_felt(item));
+        if !valid_versions.any(|item| item.is_ok_and(|item| item == version)) {
+            anyhow::bail!("Query version {value} is not listed in valid versions");
         }
-        Schema::AllOf(value) => {
-            for item in value.all_of.iter() {
-                match item {
-                    Schema::Ref(reference) => {
-                        let should_flatten = match flatten_option {
-                            FlattenOption::All => true,
-                            FlattenOption::Selected(flatten_types) => {
-                                flatten_types.contains(&reference.name().to_owned())
-                            }
-                        };
+    }
 
-                        if should_flatten {
-                            get_schema_fields(item, specs, fields, flatten_option)?;
-                        } else {
-                            fields.push(RustField {
-                                description: reference.description.to_owned(),
-                                name: reference.name().to_lowercase(),
-                                optional: false,
-                                fixed: None,
-                                arc_wrap: false,
-                                type_name: to_starknet_rs_name(reference.name()),
-                                serde_rename: None,
-                                serde_faltten: true,
-                                serializer: None,
-                            });
-                        }
+    fixed.value = format!("&{}", felt_expr(version));
+    Ok(())
+}
+
+/// Types containing themselves by value, directly or through other types, would be infinitely
+/// sized. References closing such cycles are boxed until none is left.
+fn resolve_recursive_types(types: &mut [RustType], req_types: &mut [RustType]) {
+    let mut graph = types
+        .iter()
+        .chain(req_types.iter())
+        .map(|rust_type| {
+            (
+                rust_type.name.to_owned(),
+                rust_type.content.value_references(),
+            )
+        })
+        .collect::<IndexMap<_, _>>();
+
+    // A reference from `owner` to `target` closes a cycle iff `owner` is reachable from `target`
+    let mut box_if_recursive = |owner: &str, item: &str, type_name: &mut String| {
+        let Some(target) = value_reference(type_name) else {
+            return;
+        };
+        let Some(path) = find_reference_path(&graph, target, owner) else {
+            return;
+        };
+
+        eprintln!(
+            "Recursive type detected ({} -> {}). Boxing {owner}.{item}",
+            owner,
+            path.join(" -> ")
+        );
+
+        // Boxed references no longer contribute to cycles
+        if let Some(references) = graph.get_mut(owner) {
+            if let Some(ind) = references.iter().position(|item| item == target) {
+                references.remove(ind);
+            }
+        }
+        *type_name = box_type(type_name);
+    };
+
+    for rust_type in types.iter_mut().chain(req_types.iter_mut()) {
+        let owner = rust_type.name.as_str();
+        match &mut rust_type.content {
+            RustTypeKind::Struct(inner) => {
+                for field in inner.fields.iter_mut() {
+                    if field.fixed.is_none() && !field.wrapper.is_some_and(|item| item.is_pointer())
+                    {
+                        box_if_recursive(owner, &field.name, &mut field.type_name);
                     }
-                    _ => {
-                        // We don't have a choice but to flatten it
-                        get_schema_fields(item, specs, fields, flatten_option)?;
+                }
+            }
+            RustTypeKind::Enum(inner) => {
+                for variant in inner.variants.iter_mut() {
+                    if let Some(payload) = &mut variant.payload {
+                        box_if_recursive(owner, &variant.name, &mut payload.type_name);
                     }
                 }
             }
+            RustTypeKind::Wrapper(inner) => {
+                box_if_recursive(owner, "0", &mut inner.type_name);
+            }
+            _ => {}
         }
-        Schema::Primitive(Primitive::Object(value)) => {
-            for (name, prop_value) in value.properties.iter() {
-                // For fields we keep things simple and only use one line
-                let doc_string = match prop_value.description() {
-                    Some(text) => Some(text),
-                    None => match prop_value.title() {
-                        Some(text) => Some(text),
-                        None => prop_value.summary(),
-                    },
-                };
+    }
+}
 
-                let field_type = get_rust_type_for_field(prop_value, specs)?;
+/// Shortest chain of by-value references leading from `from` to `to`, both included.
+fn find_reference_path(
+    graph: &IndexMap<String, Vec<String>>,
+    from: &str,
+    to: &str,
+) -> Option<Vec<String>> {
+    let mut parents = HashMap::<&str, &str>::new();
+    let mut queue = VecDeque::from([from]);
+
+    while let Some(current) = queue.pop_front() {
+        if current == to {
+            let mut path = vec![current.to_owned()];
+            let mut current = current;
+            while let Some(parent) = parents.get(current) {
+                path.push(parent.to_string());
+                current = parent;
+            }
+            path.reverse();
+            return Some(path);
+        }
 
-                let field_name = to_rust_field_name(name);
-                let rename = if name == &field_name {
-                    None
-                } else {
-                    Some(name.to_owned())
-                };
+        for next in graph.get(current).into_iter().flatten() {
+            if next != from && !parents.contains_key(next.as_str()) {
+                parents.insert(next, current);
+                queue.push_back(next);
+            }
+        }
+    }
 
-                // Optional field transformation
-                let field_optional = match &value.required {
-                    Some(required) => !required.contains(name),
-                    None => false,
-                };
-                let type_name = if field_optional {
-                    format!("Option<{}>", field_type.type_name)
-                } else {
-                    field_type.type_name
-                };
-                let serializer = if field_optional {
-                    field_type.serializer.map(|value| value.to_optional())
+    None
+}
+
+/// Builds accessor traits for flattened schemas out of the fields the implementing structs got
+/// from them. Fields that are fixed in any of the structs are left out.
+fn resolve_fragment_traits(
+    specs: &Specification,
+    types: &[RustType],
+    req_types: &[RustType],
+    options: &ProfileOptions,
+) -> Result<Vec<RustTrait>> {
+    let mut traits = vec![];
+
+    for fragment in options.fragment_traits.iter() {
+        let schema = match specs.components.schemas.get(fragment) {
+            Some(schema) => schema,
+            None => anyhow::bail!("Fragment schema not found: {fragment}"),
+        };
+
+        let name = to_type_name(fragment, &options.naming);
+        if types
+            .iter()
+            .chain(req_types.iter())
+            .any(|item| item.name == name)
+        {
+            anyhow::bail!("Trait for fragment {fragment} conflicts with an existing type: {name}");
+        }
+
+        let implementors = types
+            .iter()
+            .chain(req_types.iter())
+            .filter_map(|item| match &item.content {
+                RustTypeKind::Struct(inner)
+                    if inner
+                        .fields
+                        .iter()
+                        .any(|field| field.fragments.contains(fragment)) =>
+                {
+                    Some((item.name.as_str(), inner))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let (_, first_implementor) = match implementors.first() {
+            Some(implementor) => implementor,
+            None => anyhow::bail!("Fragment {fragment} is not flattened into any generated struct"),
+        };
+
+        let mut fields = vec![];
+        'fields: for field in first_implementor
+            .fields
+            .iter()
+            .filter(|field| field.fragments.contains(fragment))
+        {
+            for (implementor_name, implementor) in implementors.iter() {
+                let implementor_field = implementor
+                    .fields
+                    .iter()
+                    .find(|item| item.name == field.name && item.fragments.contains(fragment));
+                match implementor_field {
+                    Some(item) if item.fixed.is_some() => continue 'fields,
+                    Some(item) if item.accessor_type() == field.accessor_type() => {}
+                    _ => anyhow::bail!(
+                        "Field {} from fragment {fragment} differs in {implementor_name}",
+                        field.name
+                    ),
+                }
+            }
+
+            if has_lifetime(&field.accessor_type()) {
+                anyhow::bail!(
+                    "Borrowed field {} is not supported in fragment traits: {fragment}",
+                    field.name
+                );
+            }
+
+            fields.push(field.to_owned());
+        }
+
+        traits.push(RustTrait {
+            name,
+            title: schema.title().map(|value| to_starknet_rs_doc(value, true)),
+            fragment: fragment.to_owned(),
+            fields,
+            implementors: implementors
+                .iter()
+                .map(|(implementor_name, implementor)| {
+                    (implementor_name.to_string(), implementor.lifetime)
+                })
+                .collect(),
+        });
+    }
+
+    Ok(traits)
+}
+
+/// Maps the fields of conversion targets to the source fields with the same names, falling back to
+/// the fixed values of the source, `None`, or method parameters in that order.
+fn resolve_conversions(
+    types: &mut [RustType],
+    req_types: &mut [RustType],
+    conversions: &[TypeConversion],
+) -> Result<()> {
+    for conversion in conversions.iter() {
+        let find_struct = |name: &str| {
+            types
+                .iter()
+                .chain(req_types.iter())
+                .find_map(|item| match &item.content {
+                    RustTypeKind::Struct(inner) if item.name == name => Some(inner),
+                    _ => None,
+                })
+                .ok_or_else(|| anyhow::anyhow!("Conversion type is not a generated struct: {name}"))
+        };
+        let source = find_struct(&conversion.from)?;
+        let target = find_struct(&conversion.to)?;
+
+        let has_query_version = |item: &RustStruct| {
+            item.fields.iter().any(|field| {
+                field
+                    .fixed
+                    .as_ref()
+                    .is_some_and(|fixed| fixed.is_query_version)
+            })
+        };
+
+        let mut params = vec![];
+        let mut fields = vec![];
+        for field in target.fields.iter().filter(|field| field.fixed.is_none()) {
+            let field_type = match field.wrapper {
+                Some(wrapper) => wrapper.wrap_type(&field.type_name),
+                None => field.type_name.to_owned(),
+            };
+            let source_field = source.fields.iter().find(|item| item.name == field.name);
+
+            let value = match source_field {
+                Some(source_field @ RustField { fixed: None, .. }) => {
+                    // `Cow` fields are turned into owned ones and the other way around, while
+                    // smart pointers can only be added
+                    let is_cow = |item: &RustField| item.wrapper == Some(FieldWrapper::Cow);
+                    let source_pointer = source_field.wrapper.filter(|item| item.is_pointer());
+                    let target_pointer = field.wrapper.filter(|item| item.is_pointer());
+                    if source_pointer != target_pointer && source_pointer.is_some() {
+                        anyhow::bail!(
+                            "Incompatible wrapping of field {} in conversion {} -> {}",
+                            field.name,
+                            conversion.from,
+                            conversion.to
+                        );
+                    }
+
+                    let wrapper = target_pointer.filter(|_| source_pointer.is_none());
+                    let is_option = source_field.type_name != field.type_name;
+                    if is_option
+                        && (field.option_inner_type() != Some(source_field.type_name.as_str())
+                            || wrapper.is_some())
+                    {
+                        anyhow::bail!(
+                            "Incompatible types of field {} in conversion {} -> {}: {} and {}",
+                            field.name,
+                            conversion.from,
+                            conversion.to,
+                            source_field.type_name,
+                            field.type_name
+                        );
+                    }
+
+                    let is_source_option = source_field.option_inner_type().is_some();
+                    match (is_cow(source_field), is_cow(field)) {
+                        (true, false) => ConversionValue::Moved {
+                            wrapper,
+                            is_option,
+                            method: Some(if is_source_option {
+                                "map(alloc::borrow::Cow::into_owned)"
+                            } else {
+                                "into_owned()"
+                            }),
+                        },
+                        (false, true) if is_source_option => ConversionValue::Moved {
+                            wrapper,
+                            is_option,
+                            method: Some("map(alloc::borrow::Cow::Owned)"),
+                        },
+                        (false, true) => ConversionValue::Moved {
+                            wrapper: Some(FieldWrapper::Cow),
+                            is_option,
+                            method: None,
+                        },
+                        _ => ConversionValue::Moved {
+                            wrapper,
+                            is_option,
+                            method: None,
+                        },
+                    }
+                }
+                Some(RustField {
+                    fixed: Some(fixed), ..
+                }) if !fixed.is_query_version => {
+                    let value = fixed.value.trim_start_matches('&');
+                    ConversionValue::Expression(match field_type.as_str() {
+                        "String" => format!("String::from({value})"),
+                        "Option<String>" => format!("Some(String::from({value}))"),
+                        _ if field.option_inner_type().is_some() => format!("Some({value})"),
+                        _ => value.to_owned(),
+                    })
+                }
+                _ if field.option_inner_type().is_some() => {
+                    ConversionValue::Expression(String::from("None"))
+                }
+                _ => {
+                    params.push(format!("{}: {}", escape_name(&field.name), field_type));
+                    ConversionValue::Param
+                }
+            };
+
+            fields.push((field.name.to_owned(), value));
+        }
+
+        if has_query_version(target) {
+            fields.push((
+                String::from("is_query"),
+                if has_query_version(source) {
+                    ConversionValue::Moved {
+                        wrapper: None,
+                        is_option: false,
+                        method: None,
+                    }
                 } else {
-                    field_type.serializer
+                    ConversionValue::Expression(String::from("false"))
+                },
+            ));
+        }
+
+        let conversion_item = RustConversion {
+            target: conversion.to.to_owned(),
+            method: conversion.method.clone().or_else(|| {
+                (!params.is_empty()).then(|| format!("into_{}", to_snake_case(&conversion.to)))
+            }),
+            params,
+            fields,
+            drops_query: has_query_version(source) && !has_query_version(target),
+            target_lifetime: target.lifetime,
+        };
+
+        if let Some(RustTypeKind::Struct(source)) = types
+            .iter_mut()
+            .chain(req_types.iter_mut())
+            .find(|item| item.name == conversion.from)
+            .map(|item| &mut item.content)
+        {
+            source.conversions.push(conversion_item);
+        }
+    }
+
+    Ok(())
+}
+
+/// Types with borrowed fields are made generic over `'a`, and so is any type containing them,
+/// including the manually implemented ones in `manual_types` (mapped to the types they hold).
+/// Returns the names of all the types with a lifetime.
+fn resolve_lifetimes(
+    types: &mut [RustType],
+    req_types: &mut [RustType],
+    manual_types: &IndexMap<String, Vec<String>>,
+) -> Result<HashSet<String>> {
+    let mut lifetime_types = HashSet::new();
+
+    loop {
+        let mut changed = false;
+
+        for (name, references) in manual_types.iter() {
+            if !lifetime_types.contains(name)
+                && references.iter().any(|item| lifetime_types.contains(item))
+            {
+                lifetime_types.insert(name.to_owned());
+                changed = true;
+            }
+        }
+
+        for rust_type in types.iter_mut().chain(req_types.iter_mut()) {
+            if lifetime_types.contains(&rust_type.name) {
+                continue;
+            }
+
+            let borrows =
+                |type_name: &str| identifiers(type_name).any(|item| lifetime_types.contains(item));
+            let lifetime = match &mut rust_type.content {
+                RustTypeKind::Struct(inner) => {
+                    inner.lifetime = inner.fields.iter().any(|field| {
+                        field.wrapper == Some(FieldWrapper::Cow) || borrows(&field.type_name)
+                    });
+                    inner.lifetime
+                }
+                RustTypeKind::Enum(inner) => {
+                    inner.lifetime = inner.variants.iter().any(|variant| {
+                        variant
+                            .payload
+                            .as_ref()
+                            .is_some_and(|payload| borrows(&payload.type_name))
+                    });
+                    inner.lifetime
+                }
+                RustTypeKind::Wrapper(inner) => {
+                    inner.lifetime = borrows(&inner.type_name);
+                    inner.lifetime
+                }
+                _ => false,
+            };
+
+            if lifetime {
+                lifetime_types.insert(rust_type.name.to_owned());
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    for rust_type in types.iter_mut().chain(req_types.iter_mut()) {
+        match &mut rust_type.content {
+            RustTypeKind::Struct(inner) => {
+                for field in inner.fields.iter_mut() {
+                    field.type_name = add_lifetimes(&field.type_name, &lifetime_types);
+                }
+            }
+            RustTypeKind::Enum(inner) => {
+                for payload in inner
+                    .variants
+                    .iter_mut()
+                    .filter_map(|variant| variant.payload.as_mut())
+                {
+                    payload.type_name = add_lifetimes(&payload.type_name, &lifetime_types);
+                }
+            }
+            RustTypeKind::Wrapper(inner) => {
+                inner.type_name = add_lifetimes(&inner.type_name, &lifetime_types);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(lifetime_types)
+}
+
+fn schema_to_rust_type_kind(
+    specs: &Specification,
+    entity: &Schema,
+    context: &mut ResolutionContext,
+    name: &str,
+) -> Result<Option<RustTypeKind>> {
+    if let Some((type_name, value)) = get_const_value(entity)? {
+        return Ok(Some(RustTypeKind::Constant(RustConstant {
+            type_name,
+            value,
+        })));
+    }
+
+    Ok(match entity {
+        Schema::Ref(reference) => {
+            let mut fields = vec![];
+            let redirected_schema = specs
+                .components
+                .schemas
+                .get(reference.name())
+                .ok_or_else(|| anyhow::anyhow!(""))?;
+            context.flattening.push(reference.name().to_owned());
+            get_schema_fields(redirected_schema, specs, &mut fields, context, name)?;
+            context.flattening.pop();
+            Some(RustTypeKind::Struct(RustStruct {
+                serde_as_array: false,
+                extra_ref_type: false,
+                builder: false,
+                lifetime: false,
+                fields,
+                conversions: vec![],
+            }))
+        }
+        Schema::OneOf(_) | Schema::AnyOf(_) => None,
+        Schema::Primitive(Primitive::Object(object)) if is_map_object(object) => {
+            let map_type = get_rust_map_type(object, specs, context, name)?;
+            Some(RustTypeKind::Wrapper(RustWrapper {
+                type_name: map_type.type_name,
+                serializer: map_type.serializer,
+                lifetime: false,
+            }))
+        }
+        Schema::AllOf(_) | Schema::Primitive(Primitive::Object(_)) => {
+            let mut fields = vec![];
+            get_schema_fields(entity, specs, &mut fields, context, name)?;
+            Some(RustTypeKind::Struct(RustStruct {
+                serde_as_array: false,
+                extra_ref_type: false,
+                builder: false,
+                lifetime: false,
+                fields,
+                conversions: vec![],
+            }))
+        }
+        Schema::Primitive(Primitive::String(value)) => match &value.r#enum {
+            Some(variants) => Some(RustTypeKind::Enum(RustEnum {
+                is_error: false,
+                lifetime: false,
+                unknown_variant: false,
+                variants: variants
+                    .iter()
+                    .map(|item| RustVariant {
+                        description: None,
+                        name: to_variant_name(name, item, &context.options.naming),
+                        serde_name: Some(item.to_owned()),
+                        error_text: None,
+                        payload: None,
+                        bool_value: None,
+                    })
+                    .collect(),
+            })),
+            None => match &value.pattern {
+                Some(pattern) => {
+                    // Generated code runs an automaton built here instead of a regex engine
+                    let dfa = PatternDfa::new(pattern)
+                        .with_context(|| format!("Invalid pattern: {pattern}"))?;
+                    if !dfa.is_satisfiable() {
+                        anyhow::bail!("Pattern matches nothing: {pattern}");
+                    }
+
+                    Some(RustTypeKind::PatternString(RustPatternString {
+                        pattern: pattern.to_owned(),
+                        dfa,
+                    }))
+                }
+                None => {
+                    anyhow::bail!("Unexpected non-enum string type when generating struct/enum");
+                }
+            },
+        },
+        _ => {
+            anyhow::bail!("Unexpected schema type when generating struct/enum");
+        }
+    })
+}
+
+/// Method results are always given a named type, even when they're just a reference or primitive
+fn method_result_to_rust_type_kind(
+    specs: &Specification,
+    schema: &Schema,
+    context: &mut ResolutionContext,
+    name: &str,
+) -> Result<RustTypeKind> {
+    Ok(match schema {
+        Schema::OneOf(OneOf {
+            one_of: variants, ..
+        })
+        | Schema::AnyOf(AnyOf {
+            any_of: variants, ..
+        }) => RustTypeKind::Enum(one_of_to_rust_enum(specs, variants, context, name)?),
+        Schema::AllOf(_) | Schema::Primitive(Primitive::Object(_)) => {
+            let mut fields = vec![];
+            get_schema_fields(schema, specs, &mut fields, context, name)?;
+            RustTypeKind::Struct(RustStruct {
+                serde_as_array: false,
+                extra_ref_type: false,
+                builder: false,
+                lifetime: false,
+                fields,
+                conversions: vec![],
+            })
+        }
+        _ => {
+            let field_type = get_rust_type_for_field(schema, specs, context, name)?;
+            RustTypeKind::Wrapper(RustWrapper {
+                type_name: field_type.type_name,
+                serializer: field_type.serializer,
+                lifetime: false,
+            })
+        }
+    })
+}
+
+/// Turns a `oneOf` or `anyOf` into an untagged enum, hoisting any anonymous variants into their own
+/// types
+fn one_of_to_rust_enum(
+    specs: &Specification,
+    variants: &[Schema],
+    context: &mut ResolutionContext,
+    name: &str,
+) -> Result<RustEnum> {
+    Ok(RustEnum {
+        is_error: false,
+        lifetime: false,
+        unknown_variant: false,
+        variants: variants
+            .iter()
+            .enumerate()
+            .map(|(ind, variant)| {
+                let variant_name = match (variant, variant.title()) {
+                    (Schema::Ref(reference), _) => {
+                        to_type_name(reference.name(), &context.options.naming)
+                    }
+                    (_, Some(title)) => {
+                        to_type_identifier(&to_pascal_case(&title.replace(' ', "_")))
+                    }
+                    (_, None) => format!("Variant{ind}"),
                 };
 
-                fields.push(RustField {
-                    description: doc_string.map(|value| to_starknet_rs_doc(value, false)),
-                    name: field_name,
-                    optional: field_optional,
-                    fixed: None,
-                    arc_wrap: false,
-                    type_name,
-                    serde_rename: rename,
-                    serde_faltten: false,
-                    serializer,
-                });
+                let hoisted_name = format!("{name}{variant_name}");
+                let payload = get_rust_type_for_field(variant, specs, context, &hoisted_name)?;
+
+                Ok(RustVariant {
+                    description: variant
+                        .description()
+                        .or_else(|| variant.title())
+                        .map(|value| to_starknet_rs_doc(value, false)),
+                    name: variant_name,
+                    serde_name: None,
+                    error_text: None,
+                    payload: Some(payload),
+                    bool_value: bool_schema_value(variant),
+                })
+            })
+            .collect::<Result<_>>()?,
+    })
+}
+
+/// The only value allowed by a boolean schema, from its `const` or a single-value `enum`.
+fn bool_schema_value(schema: &Schema) -> Option<bool> {
+    match schema {
+        Schema::Primitive(Primitive::Boolean(value)) => match (value.r#const, &value.r#enum) {
+            (Some(value), _) => Some(value),
+            (None, Some(values))
+                if !values.is_empty() && values.iter().all(|v| v == &values[0]) =>
+            {
+                Some(values[0])
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Turns [FlattenOption::Auto] into an explicit list of schemas to be flattened
+fn resolve_flatten_option(
+    specs: &Specification,
+    flatten_option: &FlattenOption,
+) -> ResolvedFlattenOption {
+    match flatten_option {
+        FlattenOption::All => ResolvedFlattenOption::All,
+        FlattenOption::Selected(flatten_types) => {
+            ResolvedFlattenOption::Selected(flatten_types.to_owned())
+        }
+        FlattenOption::Auto(exceptions) => {
+            // When flattening everything, the flatten-only schemas are exactly the `allOf`
+            // fragments that are never used standalone.
+            let mut flatten_types = get_flatten_only_schemas(specs, &ResolvedFlattenOption::All);
+
+            flatten_types.retain(|item| !exceptions.no_flatten.contains(item));
+            for item in exceptions.flatten.iter() {
+                if !flatten_types.contains(item) {
+                    flatten_types.push(item.to_owned());
+                }
+            }
+            flatten_types.sort();
+
+            ResolvedFlattenOption::Selected(flatten_types)
+        }
+    }
+}
+
+impl ResolvedFlattenOption {
+    fn should_flatten(&self, type_name: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Selected(flatten_types) => flatten_types.iter().any(|item| item == type_name),
+        }
+    }
+}
+
+/// Finds the list of schemas that are used and only used for flattening inside objects
+fn get_flatten_only_schemas(
+    specs: &Specification,
+    flatten_option: &ResolvedFlattenOption,
+) -> Vec<String> {
+    let mut flatten_fields = HashSet::<String>::new();
+    let mut non_flatten_fields = HashSet::<String>::new();
+
+    for (_, schema) in specs.components.schemas.iter() {
+        visit_schema_for_flatten_only(
+            schema,
+            flatten_option,
+            &mut flatten_fields,
+            &mut non_flatten_fields,
+        );
+    }
+
+    // Schemas used directly by methods are never flatten-only
+    for method in specs.methods.iter() {
+        for schema in method
+            .params
+            .iter()
+            .map(|param| &param.schema)
+            .chain(std::iter::once(&method.result.schema))
+        {
+            match schema {
+                Schema::Ref(reference) => {
+                    non_flatten_fields.insert(reference.name().to_owned());
+                }
+                _ => visit_schema_for_flatten_only(
+                    schema,
+                    flatten_option,
+                    &mut flatten_fields,
+                    &mut non_flatten_fields,
+                ),
+            }
+        }
+    }
+
+    let mut flatten_only_fields = flatten_fields
+        .into_iter()
+        .filter(|item| !non_flatten_fields.contains(item))
+        .collect::<Vec<_>>();
+    flatten_only_fields.sort();
+
+    flatten_only_fields
+}
+
+fn visit_schema_for_flatten_only(
+    schema: &Schema,
+    flatten_option: &ResolvedFlattenOption,
+    flatten_fields: &mut HashSet<String>,
+    non_flatten_fields: &mut HashSet<String>,
+) {
+    match schema {
+        Schema::OneOf(OneOf {
+            one_of: variants, ..
+        })
+        | Schema::AnyOf(AnyOf {
+            any_of: variants, ..
+        }) => {
+            // Recursion
+            for variant in variants.iter() {
+                match variant {
+                    Schema::Ref(reference) => {
+                        non_flatten_fields.insert(reference.name().to_owned());
+                    }
+                    _ => visit_schema_for_flatten_only(
+                        variant,
+                        flatten_option,
+                        flatten_fields,
+                        non_flatten_fields,
+                    ),
+                }
+            }
+        }
+        Schema::AllOf(all_of) => {
+            for fragment in all_of.all_of.iter() {
+                match fragment {
+                    Schema::Ref(reference) => {
+                        let should_flatten = flatten_option.should_flatten(reference.name());
+
+                        if should_flatten {
+                            flatten_fields.insert(reference.name().to_owned());
+                        } else {
+                            non_flatten_fields.insert(reference.name().to_owned());
+                            visit_schema_for_flatten_only(
+                                fragment,
+                                flatten_option,
+                                flatten_fields,
+                                non_flatten_fields,
+                            );
+                        }
+                    }
+                    _ => visit_schema_for_flatten_only(
+                        fragment,
+                        flatten_option,
+                        flatten_fields,
+                        non_flatten_fields,
+                    ),
+                }
+            }
+        }
+        Schema::Not(not) => visit_schema_for_flatten_only(
+            &not.not,
+            flatten_option,
+            flatten_fields,
+            non_flatten_fields,
+        ),
+        Schema::Primitive(Primitive::Object(object)) => {
+            let additional_properties = match &object.additional_properties {
+                Some(AdditionalProperties::Schema(schema)) => Some(schema.as_ref()),
+                _ => None,
+            };
+
+            for prop_type in object.properties.values().chain(additional_properties) {
+                match prop_type {
+                    Schema::Ref(reference) => {
+                        non_flatten_fields.insert(reference.name().to_owned());
+                    }
+                    _ => visit_schema_for_flatten_only(
+                        prop_type,
+                        flatten_option,
+                        flatten_fields,
+                        non_flatten_fields,
+                    ),
+                }
+            }
+        }
+        Schema::Primitive(Primitive::Array(array)) => match array.items.as_ref() {
+            Schema::Ref(reference) => {
+                non_flatten_fields.insert(reference.name().to_owned());
+            }
+            _ => visit_schema_for_flatten_only(
+                &array.items,
+                flatten_option,
+                flatten_fields,
+                non_flatten_fields,
+            ),
+        },
+        _ => {}
+    }
+}
+
+fn get_schema_fields(
+    schema: &Schema,
+    specs: &Specification,
+    fields: &mut Vec<RustField>,
+    context: &mut ResolutionContext,
+    parent_name: &str,
+) -> Result<()> {
+    match schema {
+        Schema::Ref(value) => {
+            let ref_type_name = value.name();
+            let ref_type = match specs.components.schemas.get(ref_type_name) {
+                Some(ref_type) => ref_type,
+                None => anyhow::bail!("Ref target type not found: {}", ref_type_name),
+            };
+
+            if context.flattening.iter().any(|item| item == ref_type_name) {
+                anyhow::bail!(
+                    "Schema flattens itself: {} -> {}",
+                    context.flattening.join(" -> "),
+                    ref_type_name
+                );
+            }
+
+            // Schema redirection. Anonymous types inside are named after the referenced schema so
+            // that they're shared by all the types flattening it.
+            context.flattening.push(ref_type_name.to_owned());
+            get_schema_fields(
+                ref_type,
+                specs,
+                fields,
+                context,
+                &to_type_name(ref_type_name, &context.options.naming),
+            )?;
+            context.flattening.pop();
+        }
+        Schema::AllOf(value) => {
+            for item in value.all_of.iter() {
+                match item {
+                    Schema::Ref(reference) => {
+                        let should_flatten =
+                            context.flatten_option.should_flatten(reference.name());
+
+                        if should_flatten {
+                            get_schema_fields(item, specs, fields, context, parent_name)?;
+                        } else {
+                            fields.push(RustField {
+                                description: reference.description.to_owned(),
+                                name: reference.name().to_lowercase(),
+                                optional: false,
+                                fixed: None,
+                                wrapper: None,
+                                type_name: to_type_name(reference.name(), &context.options.naming),
+                                serde_rename: None,
+                                serde_faltten: true,
+                                serializer: None,
+                                deprecation: context.spec_deprecation(
+                                    reference.annotations.deprecated,
+                                    reference.description.as_ref(),
+                                ),
+                                ref_type: None,
+                                fragments: context.flattening.clone(),
+                                pattern: None,
+                            });
+                        }
+                    }
+                    _ => {
+                        // We don't have a choice but to flatten it
+                        get_schema_fields(item, specs, fields, context, parent_name)?;
+                    }
+                }
+            }
+        }
+        Schema::Primitive(Primitive::Object(value)) => {
+            for (name, prop_value) in value.properties.iter() {
+                // For fields we keep things simple and only use one line
+                let doc_string = match prop_value.description() {
+                    Some(text) => Some(text),
+                    None => match prop_value.title() {
+                        Some(text) => Some(text),
+                        None => prop_value.summary(),
+                    },
+                };
+
+                let hoisted_name = context.type_name(parent_name, name);
+                let field_type =
+                    get_rust_type_for_field(prop_value, specs, context, &hoisted_name)?;
+
+                let field_name = to_field_name(parent_name, name, &context.options.naming);
+                let rename = if name == &field_name {
+                    None
+                } else {
+                    Some(name.to_owned())
+                };
+
+                // Optional field transformation
+                let field_optional = match &value.required {
+                    Some(required) => !required.contains(name),
+                    None => false,
+                };
+                let type_name = if field_optional {
+                    format!("Option<{}>", field_type.type_name)
+                } else {
+                    field_type.type_name
+                };
+                let serializer = if field_optional {
+                    field_type.serializer.map(|value| value.to_optional())
+                } else {
+                    field_type.serializer
+                };
+
+                let fixed = if context.options.fixed_field_types.infer_from_spec {
+                    get_fixed_value(prop_value, specs, context.options)?.map(
+                        |(value, is_query_version)| FixedField {
+                            name: field_name.clone(),
+                            value,
+                            is_query_version,
+                            must_present_in_deser: false,
+                        },
+                    )
+                } else {
+                    None
+                };
+
+                fields.push(RustField {
+                    description: doc_string.map(|value| to_starknet_rs_doc(value, false)),
+                    name: field_name,
+                    optional: field_optional,
+                    fixed,
+                    wrapper: None,
+                    type_name,
+                    serde_rename: rename,
+                    serde_faltten: false,
+                    serializer,
+                    deprecation: context.spec_deprecation(
+                        prop_value.annotations().deprecated,
+                        prop_value.description(),
+                    ),
+                    ref_type: None,
+                    fragments: context.flattening.clone(),
+                    pattern: get_string_pattern(prop_value),
+                });
+            }
+
+            if matches!(
+                value.additional_properties,
+                Some(AdditionalProperties::Allowed(true)) | Some(AdditionalProperties::Schema(_))
+            ) {
+                // The map is flattened so its Rust name never shows up on the wire, but it must not
+                // clash with a property
+                if let Some(field) = fields
+                    .iter()
+                    .find(|field| field.name == ADDITIONAL_PROPERTIES_FIELD)
+                {
+                    anyhow::bail!(
+                        "Property {} of {parent_name} conflicts with the field holding \
+                         additionalProperties",
+                        field.serde_rename.as_ref().unwrap_or(&field.name)
+                    );
+                }
+
+                let map_type = get_rust_map_type(
+                    value,
+                    specs,
+                    context,
+                    &context.type_name(parent_name, ADDITIONAL_PROPERTIES_FIELD),
+                )?;
+
+                fields.push(RustField {
+                    description: Some(String::from("Properties not covered by other fields")),
+                    name: String::from(ADDITIONAL_PROPERTIES_FIELD),
+                    optional: false,
+                    fixed: None,
+                    wrapper: None,
+                    type_name: map_type.type_name,
+                    serde_rename: None,
+                    serde_faltten: true,
+                    serializer: map_type.serializer,
+                    deprecation: None,
+                    ref_type: None,
+                    fragments: context.flattening.clone(),
+                    pattern: None,
+                });
+            }
+        }
+        _ => {
+            dbg!(schema);
+            anyhow::bail!("Unexpected schema type when getting object fields");
+        }
+    }
+
+    Ok(())
+}
+
+/// Anonymous objects, `oneOf`s and `allOf`s are hoisted into their own types named `hoisted_name`.
+fn get_rust_type_for_field(
+    schema: &Schema,
+    specs: &Specification,
+    context: &mut ResolutionContext,
+    hoisted_name: &str,
+) -> Result<RustFieldType> {
+    match schema {
+        Schema::Ref(value) => {
+            let ref_type_name = value.name();
+            if !specs.components.schemas.contains_key(ref_type_name) {
+                anyhow::bail!("Ref target type not found: {}", ref_type_name);
+            }
+
+            // Hard-coded special rules
+            if let Some(field_type) = context.field_type_override(ref_type_name) {
+                return Ok(field_type);
+            }
+
+            // Constants are generated as `const` items instead of types
+            let ref_type = &specs.components.schemas[ref_type_name];
+            if get_const_value(ref_type)?.is_some() {
+                return get_rust_type_for_field(ref_type, specs, context, hoisted_name);
+            }
+
+            Ok(RustFieldType {
+                type_name: to_type_name(ref_type_name, &context.options.naming),
+                serializer: None,
+            })
+        }
+        Schema::OneOf(OneOf {
+            one_of: variants, ..
+        })
+        | Schema::AnyOf(AnyOf {
+            any_of: variants, ..
+        }) => {
+            context.hoist(schema, hoisted_name, |context| {
+                Ok(RustTypeKind::Enum(one_of_to_rust_enum(
+                    specs,
+                    variants,
+                    context,
+                    hoisted_name,
+                )?))
+            })?;
+
+            Ok(RustFieldType {
+                type_name: hoisted_name.to_owned(),
+                serializer: None,
+            })
+        }
+        // There's no way to express negation in the type system
+        Schema::Not(_) => Ok(RustFieldType {
+            type_name: String::from("serde_json::Value"),
+            serializer: None,
+        }),
+        Schema::Primitive(Primitive::Object(object)) if is_map_object(object) => {
+            get_rust_map_type(object, specs, context, hoisted_name)
+        }
+        Schema::AllOf(_) | Schema::Primitive(Primitive::Object(_)) => {
+            context.hoist(schema, hoisted_name, |context| {
+                let mut fields = vec![];
+                get_schema_fields(schema, specs, &mut fields, context, hoisted_name)?;
+
+                Ok(RustTypeKind::Struct(RustStruct {
+                    serde_as_array: false,
+                    extra_ref_type: false,
+                    builder: false,
+                    lifetime: false,
+                    fields,
+                    conversions: vec![],
+                }))
+            })?;
+
+            Ok(RustFieldType {
+                type_name: hoisted_name.to_owned(),
+                serializer: None,
+            })
+        }
+        Schema::Primitive(value) => match value {
+            Primitive::Array(value) => {
+                let item_type =
+                    get_rust_type_for_field(&value.items, specs, context, hoisted_name)?;
+
+                // Arrays with a fixed length get mapped to Rust arrays. Serde only implements
+                // its traits for arrays of up to 32 elements.
+                let wrap = |item: &str| match (value.min_items, value.max_items) {
+                    (Some(min_items), Some(max_items))
+                        if min_items == max_items && max_items <= 32 =>
+                    {
+                        format!("[{item}; {max_items}]")
+                    }
+                    _ => format!("Vec<{item}>"),
+                };
+
+                let serializer = match item_type.serializer {
+                    Some(SerializerOverride::Serde(_)) => {
+                        todo!("Array wrapper for #[serde(with)] not implemented")
+                    }
+                    Some(SerializerOverride::SerdeAs(serializer)) => {
+                        Some(SerializerOverride::SerdeAs(wrap(&serializer)))
+                    }
+                    None => None,
+                };
+                Ok(RustFieldType {
+                    type_name: wrap(&item_type.type_name),
+                    serializer,
+                })
+            }
+            Primitive::Boolean(_) => Ok(RustFieldType {
+                type_name: String::from("bool"),
+                serializer: None,
+            }),
+            Primitive::Integer(value) => Ok(RustFieldType {
+                type_name: get_rust_integer_type(value, context.options.non_zero_integers)?,
+                serializer: None,
+            }),
+            Primitive::Number(_) => Ok(RustFieldType {
+                type_name: String::from("f64"),
+                serializer: None,
+            }),
+            Primitive::Null(_) => Ok(RustFieldType {
+                type_name: String::from("()"),
+                serializer: None,
+            }),
+            Primitive::Object(_) => unreachable!("Anonymous objects are hoisted above"),
+            Primitive::String(value) => {
+                // Hacky solution but it's the best we can do given the specs
+                if let Some(desc) = &value.description {
+                    if desc.contains("base64") {
+                        return Ok(RustFieldType {
+                            type_name: String::from("Vec<u8>"),
+                            serializer: Some(SerializerOverride::Serde(String::from("base64"))),
+                        });
+                    }
+                }
+
+                Ok(RustFieldType {
+                    type_name: String::from("String"),
+                    serializer: None,
+                })
+            }
+        },
+    }
+}
+
+/// Pattern of an inline string schema, or of the items of an inline array of strings.
+fn get_string_pattern(schema: &Schema) -> Option<String> {
+    match schema {
+        Schema::Primitive(Primitive::String(value)) => value.pattern.to_owned(),
+        Schema::Primitive(Primitive::Array(value)) => get_string_pattern(&value.items),
+        _ => None,
+    }
+}
+
+/// Objects without properties but with `additionalProperties` are plain maps.
+fn is_map_object(object: &ObjectPrimitive) -> bool {
+    object.properties.is_empty()
+        && matches!(
+            object.additional_properties,
+            Some(AdditionalProperties::Allowed(true)) | Some(AdditionalProperties::Schema(_))
+        )
+}
+
+/// Map type for the `additionalProperties` of an object, if any are allowed.
+fn get_rust_map_type(
+    object: &ObjectPrimitive,
+    specs: &Specification,
+    context: &mut ResolutionContext,
+    hoisted_name: &str,
+) -> Result<RustFieldType> {
+    let value_type = match &object.additional_properties {
+        Some(AdditionalProperties::Schema(schema)) => {
+            get_rust_type_for_field(schema, specs, context, &format!("{hoisted_name}Value"))?
+        }
+        _ => RustFieldType {
+            type_name: String::from("serde_json::Value"),
+            serializer: None,
+        },
+    };
+
+    Ok(RustFieldType {
+        type_name: format!(
+            "alloc::collections::BTreeMap<String, {}>",
+            value_type.type_name
+        ),
+        serializer: match value_type.serializer {
+            Some(SerializerOverride::Serde(serializer)) => anyhow::bail!(
+                "Map values of {hoisted_name} use #[serde(with = \"{serializer}\")], which can't be \
+                 wrapped in a map"
+            ),
+            Some(SerializerOverride::SerdeAs(serializer)) => Some(SerializerOverride::SerdeAs(
+                format!("alloc::collections::BTreeMap<_, {serializer}>"),
+            )),
+            None => None,
+        },
+    })
+}
+
+/// Type and literal for schemas with `const`, which are generated as `const` items.
+fn get_const_value(schema: &Schema) -> Result<Option<(String, String)>> {
+    Ok(match schema {
+        Schema::Primitive(Primitive::String(StringPrimitive {
+            r#const: Some(value),
+            ..
+        })) => Some((String::from("&str"), format!("{value:?}"))),
+        Schema::Primitive(Primitive::Integer(
+            value @ IntegerPrimitive {
+                r#const: Some(const_value),
+                ..
+            },
+        )) => Some((
+            get_rust_integer_type(value, false)?,
+            const_value.to_string(),
+        )),
+        Schema::Primitive(Primitive::Boolean(BooleanPrimitive {
+            r#const: Some(value),
+            ..
+        })) => Some((String::from("bool"), value.to_string())),
+        _ => None,
+    })
+}
+
+/// Literal a property is fixed to, from a single-value string `enum` or a `const` (possibly behind
+/// a reference), and whether it's a transaction version with a query-only counterpart. Non-string
+/// literals are borrowed like the ones configured in profiles.
+fn get_fixed_value(
+    schema: &Schema,
+    specs: &Specification,
+    options: &ProfileOptions,
+) -> Result<Option<(String, bool)>> {
+    Ok(match schema {
+        Schema::Ref(reference) => match specs.components.schemas.get(reference.name()) {
+            Some(ref_type) if get_const_value(ref_type)?.is_some() => {
+                get_fixed_value(ref_type, specs, options)?
+            }
+            _ => None,
+        },
+        Schema::Primitive(Primitive::String(StringPrimitive {
+            r#enum: Some(values),
+            r#const: None,
+            ..
+        })) => match values.as_slice() {
+            [value] => Some((format!("{value:?}"), false)),
+            // Newer specs list query-only versions next to the transaction versions. They're kept
+            // as hex until checked against the profile.
+            [version, query_version] => {
+                let offset = parse_felt(&options.query_version.offset)?;
+                match (parse_felt(version), parse_felt(query_version)) {
+                    (Ok(parsed_version), Ok(query_version))
+                        if felt_add(parsed_version, offset) == query_version =>
+                    {
+                        Some((version.to_owned(), true))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        },
+        _ => get_const_value(schema)?.map(|(type_name, value)| {
+            if type_name == "&str" {
+                (value, false)
+            } else {
+                (format!("&{value}"), false)
+            }
+        }),
+    })
+}
+
+/// Picks the integer type allowed by `format`, or by `minimum` and `maximum` otherwise. Integers
+/// without a lower bound are assumed to be unsigned for backward compatibility.
+fn get_rust_integer_type(value: &IntegerPrimitive, non_zero: bool) -> Result<String> {
+    let type_name = match value.format.as_deref() {
+        Some("int32") => "i32",
+        Some("int64") => "i64",
+        Some("uint32") => "u32",
+        Some("uint64") => "u64",
+        Some("uint128") => "u128",
+        Some(format) => anyhow::bail!("Unsupported integer format: {format}"),
+        None => {
+            let bounds = || value.minimum.iter().chain(value.maximum.iter());
+            let is_signed = value
+                .minimum
+                .as_ref()
+                .and_then(|minimum| minimum.as_f64())
+                .is_some_and(|minimum| minimum < 0.0);
+
+            if is_signed {
+                if bounds().all(|bound| bound.is_i64()) {
+                    "i64"
+                } else if bounds().all(|bound| {
+                    bound
+                        .as_f64()
+                        .is_some_and(|bound| bound >= i128::MIN as f64 && bound < i128::MAX as f64)
+                }) {
+                    "i128"
+                } else {
+                    anyhow::bail!("Integer bounds don't fit in i128");
+                }
+            } else {
+                match &value.maximum {
+                    Some(maximum) if maximum.as_u64().is_some_and(|max| max <= u32::MAX.into()) => {
+                        "u32"
+                    }
+                    Some(maximum) if maximum.is_f64() => match maximum.as_f64() {
+                        Some(max) if max < u64::MAX as f64 => "u64",
+                        Some(max) if max <= u128::MAX as f64 => "u128",
+                        _ => anyhow::bail!("Integer maximum doesn't fit in u128: {maximum}"),
+                    },
+                    _ => "u64",
+                }
+            }
+        }
+    };
+
+    let minimum = value.minimum.as_ref().and_then(|minimum| minimum.as_f64());
+    Ok(match minimum {
+        Some(minimum) if non_zero && minimum >= 1.0 && type_name.starts_with('u') => {
+            format!("core::num::NonZero{}", type_name.to_ascii_uppercase())
+        }
+        _ => type_name.to_owned(),
+    })
+}
+
+fn get_field_type_override(type_name: &str) -> Option<RustFieldType> {
+    Some(match type_name {
+        "ADDRESS" | "STORAGE_KEY" | "TXN_HASH" | "FELT" | "BLOCK_HASH" | "CHAIN_ID"
+        | "PROTOCOL_VERSION" => RustFieldType {
+            type_name: String::from("FieldElement"),
+            serializer: Some(SerializerOverride::SerdeAs(String::from("UfeHex"))),
+        },
+        "ETH_ADDRESS" => RustFieldType {
+            type_name: String::from("EthAddress"),
+            serializer: None,
+        },
+        "EXECUTION_RESULT" => RustFieldType {
+            type_name: String::from("ExecutionResult"),
+            serializer: None,
+        },
+        "BLOCK_NUMBER" => RustFieldType {
+            type_name: String::from("u64"),
+            serializer: None,
+        },
+        "NUM_AS_HEX" => RustFieldType {
+            type_name: String::from("u64"),
+            serializer: Some(SerializerOverride::SerdeAs(String::from("NumAsHex"))),
+        },
+        "SIGNATURE" => RustFieldType {
+            type_name: String::from("Vec<FieldElement>"),
+            serializer: Some(SerializerOverride::SerdeAs(String::from("Vec<UfeHex>"))),
+        },
+        "CONTRACT_ABI" => RustFieldType {
+            type_name: String::from("Vec<LegacyContractAbiEntry>"),
+            serializer: None,
+        },
+        "CONTRACT_ENTRY_POINT_LIST" => RustFieldType {
+            type_name: String::from("Vec<ContractEntryPoint>"),
+            serializer: None,
+        },
+        "LEGACY_CONTRACT_ENTRY_POINT_LIST" => RustFieldType {
+            type_name: String::from("Vec<LegacyContractEntryPoint>"),
+            serializer: None,
+        },
+        "TXN_TYPE" => RustFieldType {
+            type_name: String::from("String"),
+            serializer: None,
+        },
+        _ => return None,
+    })
+}
+
+/// Finds the sentence stating that an item is deprecated, if any.
+fn find_deprecation_sentence(doc: &str) -> Option<String> {
+    let deprecated_regex = Regex::new(r"(?i)\b(is|are|has been|have been) deprecated\b").unwrap();
+
+    let matched = deprecated_regex.find(doc)?;
+    let start = doc[..matched.start()]
+        .rfind(['.', '\n'])
+        .map(|ind| ind + 1)
+        .unwrap_or(0);
+    let end = doc[matched.end()..]
+        .find(['.', '\n'])
+        .map(|ind| matched.end() + ind)
+        .unwrap_or(doc.len());
+
+    Some(to_starknet_rs_doc(doc[start..end].trim(), true))
+}
+
+fn identifiers(type_name: &str) -> impl Iterator<Item = &str> {
+    type_name
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|item| !item.is_empty())
+}
+
+/// Adds `<'a>` after the names of types with lifetimes in `type_name`.
+fn add_lifetimes(type_name: &str, lifetime_types: &HashSet<String>) -> String {
+    let mut result = String::new();
+    let mut identifier = String::new();
+
+    for c in type_name.chars().chain(std::iter::once(' ')) {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            identifier.push(c);
+            continue;
+        }
+
+        let is_lifetime_type = lifetime_types.contains(&identifier);
+        result.push_str(&identifier);
+        if is_lifetime_type {
+            result.push_str("<'a>");
+        }
+        identifier.clear();
+        result.push(c);
+    }
+
+    result.pop();
+    result
+}
+
+/// The type contained by value in `type_name`, i.e. not behind a pointer or a collection. Fixed-size
+/// arrays hold their items by value.
+fn value_reference(type_name: &str) -> Option<&str> {
+    if let Some((item_type, len)) = array_item_type(type_name) {
+        return if len == "0" {
+            None
+        } else {
+            value_reference(item_type)
+        };
+    }
+
+    match type_name
+        .strip_prefix("Option<")
+        .and_then(|value| value.strip_suffix('>'))
+    {
+        Some(inner) => value_reference(inner),
+        None if identifiers(type_name).eq([type_name]) => Some(type_name),
+        None => None,
+    }
+}
+
+/// Boxes the type contained by value in `type_name`, leaving the `Option` or array around it if
+/// any.
+fn box_type(type_name: &str) -> String {
+    if let Some((item_type, len)) = array_item_type(type_name) {
+        return format!("[{}; {len}]", box_type(item_type));
+    }
+
+    match type_name
+        .strip_prefix("Option<")
+        .and_then(|value| value.strip_suffix('>'))
+    {
+        Some(inner) => format!("Option<{}>", box_type(inner)),
+        None => format!("alloc::boxed::Box<{type_name}>"),
+    }
+}
+
+/// Item type and length of a fixed-size array type like `[T; N]`.
+fn array_item_type(type_name: &str) -> Option<(&str, &str)> {
+    type_name
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+        .and_then(|value| value.rsplit_once("; "))
+}
+
+/// Order of the Stark field, in little-endian 64-bit limbs like the `FieldElement` internals.
+const FELT_MODULUS: [u64; 4] = [1, 0, 0, 576460752303423505];
+
+/// Parses a hex string into a field element in little-endian 64-bit limbs.
+fn parse_felt(value: &str) -> Result<[u64; 4]> {
+    let digits = value.trim_start_matches("0x");
+    if digits.is_empty() || digits.len() > 64 {
+        anyhow::bail!("Invalid field element: {value}");
+    }
+
+    let digits = format!("{digits:0>64}");
+    let mut limbs = [0u64; 4];
+    for (ind, limb) in limbs.iter_mut().enumerate() {
+        let end = 64 - ind * 16;
+        *limb = u64::from_str_radix(&digits[(end - 16)..end], 16)
+            .map_err(|_| anyhow::anyhow!("Invalid field element: {value}"))?;
+    }
+
+    if !felt_lt(limbs, FELT_MODULUS) {
+        anyhow::bail!("Field element out of range: {value}");
+    }
+
+    Ok(limbs)
+}
+
+fn felt_lt(lhs: [u64; 4], rhs: [u64; 4]) -> bool {
+    lhs.iter().rev().lt(rhs.iter().rev())
+}
+
+/// Modular addition. Both sides are below the modulus, which leaves room for the carry.
+fn felt_add(lhs: [u64; 4], rhs: [u64; 4]) -> [u64; 4] {
+    let mut result = [0u64; 4];
+    let mut carry = false;
+    for ind in 0..4 {
+        let (sum, carry_1) = lhs[ind].overflowing_add(rhs[ind]);
+        let (sum, carry_2) = sum.overflowing_add(carry as u64);
+        result[ind] = sum;
+        carry = carry_1 || carry_2;
+    }
+
+    if felt_lt(result, FELT_MODULUS) {
+        return result;
+    }
+
+    let mut borrow = false;
+    for ind in 0..4 {
+        let (diff, borrow_1) = result[ind].overflowing_sub(FELT_MODULUS[ind]);
+        let (diff, borrow_2) = diff.overflowing_sub(borrow as u64);
+        result[ind] = diff;
+        borrow = borrow_1 || borrow_2;
+    }
+    result
+}
+
+/// Montgomery representation (times 2^256) as taken by `FieldElement::from_mont`.
+fn felt_to_mont(value: [u64; 4]) -> [u64; 4] {
+    (0..256).fold(value, |acc, _| felt_add(acc, acc))
+}
+
+/// `FieldElement` expression for a transaction version. Versions without named `FieldElement`
+/// constants get generated ones named after their hex digits.
+fn felt_expr(value: [u64; 4]) -> String {
+    match value {
+        [0, 0, 0, 0] => String::from("FieldElement::ZERO"),
+        [1, 0, 0, 0] => String::from("FieldElement::ONE"),
+        [2, 0, 0, 0] => String::from("FieldElement::TWO"),
+        [3, 0, 0, 0] => String::from("FieldElement::THREE"),
+        _ => {
+            let digits = value
+                .iter()
+                .rev()
+                .map(|limb| format!("{limb:016X}"))
+                .collect::<String>();
+            format!("TRANSACTION_VERSION_{}", digits.trim_start_matches('0'))
+        }
+    }
+}
+
+/// Inverse of `felt_expr`.
+fn felt_from_expr(expr: &str) -> Option<[u64; 4]> {
+    match expr {
+        "FieldElement::ZERO" => Some([0, 0, 0, 0]),
+        "FieldElement::ONE" => Some([1, 0, 0, 0]),
+        "FieldElement::TWO" => Some([2, 0, 0, 0]),
+        "FieldElement::THREE" => Some([3, 0, 0, 0]),
+        _ => expr
+            .strip_prefix("TRANSACTION_VERSION_")
+            .and_then(|digits| parse_felt(digits).ok()),
+    }
+}
+
+/// The type argument of a generic type like `Vec<T>`, if the type is an instance of `outer`.
+fn generic_argument<'a>(type_name: &'a str, outer: &str) -> Option<&'a str> {
+    type_name
+        .strip_prefix(outer)
+        .and_then(|value| value.strip_prefix('<'))
+        .and_then(|value| value.strip_suffix('>'))
+}
+
+fn is_integer_type(type_name: &str) -> bool {
+    let type_name = type_name.trim_start_matches("core::num::NonZero");
+    matches!(
+        type_name.to_ascii_lowercase().as_str(),
+        "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128"
+    )
+}
+
+fn has_lifetime(type_name: &str) -> bool {
+    type_name.contains("'a")
+}
+
+fn lifetime_params(lifetime: bool) -> &'static str {
+    if lifetime {
+        "<'a>"
+    } else {
+        ""
+    }
+}
+
+fn print_generated_header() {
+    println!("// AUTO-GENERATED CODE. DO NOT EDIT");
+    println!("// To change the code generated, modify the codegen tool instead:");
+    println!("//     https://github.com/xJonathanLEI/starknet-jsonrpc-codegen");
+    println!();
+    println!("// Code generated with version:");
+    match built_info::GIT_COMMIT_HASH {
+        Some(commit_hash) => {
+            println!(
+                "//     https://github.com/xJonathanLEI/starknet-jsonrpc-codegen#{commit_hash}"
+            )
+        }
+        None => println!("    <Unable to determine Git commit hash>"),
+    }
+    println!();
+}
+
+fn print_pattern_mismatch_error() {
+    println!("/// Error returned when a string doesn't match the pattern required by its type.");
+    println!("#[derive(Debug, Clone)]");
+    println!("pub struct PatternMismatchError {{");
+    println!("    pub type_name: &'static str,");
+    println!("    pub pattern: &'static str,");
+    println!("}}");
+    println!();
+    println!("#[cfg(feature = \"std\")]");
+    println!("impl std::error::Error for PatternMismatchError {{}}");
+    println!();
+    println!("impl core::fmt::Display for PatternMismatchError {{");
+    println!("    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{");
+    println!("        write!(");
+    println!("            f,");
+    println!("            \"value does not match pattern `{{}}` of `{{}}`\",");
+    println!("            self.pattern, self.type_name");
+    println!("        )");
+    println!("    }}");
+    println!("}}");
+}
+
+/// Prints a deserializer for `bool` fields that only accept `value`.
+fn print_bool_deserializer(value: bool) {
+    println!("fn deserialize_{value}<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {{");
+    println!("    let value = bool::deserialize(deserializer)?;");
+    println!("    if {}value {{", if value { "" } else { "!" });
+    println!("        Ok(value)");
+    println!("    }} else {{");
+    println!("        Err(serde::de::Error::invalid_value(");
+    println!("            serde::de::Unexpected::Bool(value),");
+    println!("            &\"{value}\",");
+    println!("        ))");
+    println!("    }}");
+    println!("}}");
+}
+
+/// Renders a builder tracking required fields in type parameters (`()` when unset), so that
+/// `build()` only exists once all of them are set.
+fn render_builder_stdout(name: &str, lifetime: bool, fields: &[RustField]) {
+    let builder_name = format!("{name}Builder");
+    let owned_type = format!("{name}{}", lifetime_params(lifetime));
+
+    let required_fields = fields
+        .iter()
+        .filter(|field| field.option_inner_type().is_none())
+        .collect::<Vec<_>>();
+    let params = (0..required_fields.len())
+        .map(|ind| format!("F{ind}"))
+        .collect::<Vec<_>>();
+    // Lifetime of borrowed fields, declared ahead of the type parameters. The builder itself only
+    // needs it for optional fields, as required ones are held in type parameters.
+    let lifetime_param = lifetime.then(|| String::from("'a"));
+    let builder_lifetime = lifetime_param.clone().filter(|_| {
+        fields
+            .iter()
+            .any(|field| field.option_inner_type().is_some() && field.borrows())
+    });
+
+    // Field types as rendered in the struct, and the types accepted by setters. Setters of `Cow`
+    // fields take the `Cow` itself so that borrowed strings can be passed.
+    let field_type = |field: &RustField| match field.wrapper {
+        Some(wrapper) => wrapper.wrap_type(&field.type_name),
+        None => field.type_name.clone(),
+    };
+    let pointer = |field: &RustField| field.wrapper.filter(|wrapper| wrapper.is_pointer());
+    let setter_type = |field: &RustField| match field.wrapper {
+        Some(FieldWrapper::Cow) => FieldWrapper::Cow.wrap_type("String"),
+        _ => field
+            .option_inner_type()
+            .unwrap_or(&field.type_name)
+            .to_owned(),
+    };
+    let setter_value = |field: &RustField| {
+        let value = escape_name(&field.name);
+        let value = if field.option_inner_type().is_some() {
+            format!("Some({value})")
+        } else {
+            value
+        };
+        match pointer(field) {
+            Some(wrapper) => wrapper.wrap_value(&value),
+            None => value,
+        }
+    };
+
+    // `impl` blocks only declare the lifetime where it's used
+    let with_lifetime = |used: bool, params: &[String]| {
+        lifetime_param
+            .iter()
+            .filter(|_| used)
+            .chain(params.iter())
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let builder_type = |args: &[String]| {
+        let args = builder_lifetime
+            .iter()
+            .chain(args.iter())
+            .cloned()
+            .collect::<Vec<_>>();
+        if args.is_empty() {
+            builder_name.clone()
+        } else {
+            format!("{}<{}>", builder_name, args.join(", "))
+        }
+    };
+    let unset_args = vec![String::from("()"); required_fields.len()];
+    let set_args = required_fields
+        .iter()
+        .map(|field| field_type(field))
+        .collect::<Vec<_>>();
+
+    print_doc(
+        &format!("Builder for [{name}]. Required fields are checked at compile time."),
+        0,
+    );
+    println!("#[derive(Debug, Clone)]");
+    println!("pub struct {} {{", builder_type(&params));
+    let mut ind_required = 0;
+    for field in fields.iter() {
+        if field.option_inner_type().is_some() {
+            println!("    {}: {},", escape_name(&field.name), field_type(field));
+        } else {
+            println!("    {}: F{},", escape_name(&field.name), ind_required);
+            ind_required += 1;
+        }
+    }
+    println!("}}");
+    println!();
+
+    print_impl_header(&with_lifetime(true, &[]), &owned_type);
+    print_doc("Creates a builder with no fields set.", 4);
+    println!("    pub fn builder() -> {} {{", builder_type(&unset_args));
+    print_struct_literal(
+        &builder_name,
+        &fields
+            .iter()
+            .map(|field| {
+                format!(
+                    "{}: {}",
+                    escape_name(&field.name),
+                    match (field.option_inner_type().is_some(), pointer(field)) {
+                        (true, Some(wrapper)) => wrapper.wrap_value("None"),
+                        (true, None) => String::from("None"),
+                        (false, _) => String::from("()"),
+                    }
+                )
+            })
+            .collect::<Vec<_>>(),
+    );
+    println!("    }}");
+    println!("}}");
+
+    for (ind_field, field) in required_fields.iter().enumerate() {
+        let mut impl_args = params.clone();
+        impl_args[ind_field] = String::from("()");
+        let mut return_args = params.clone();
+        return_args[ind_field] = field_type(field);
+        let impl_params = params
+            .iter()
+            .enumerate()
+            .filter(|(ind, _)| *ind != ind_field)
+            .map(|(_, param)| param.to_owned())
+            .collect::<Vec<_>>();
+
+        println!();
+        print_impl_header(
+            &with_lifetime(builder_lifetime.is_some() || field.borrows(), &impl_params),
+            &builder_type(&impl_args),
+        );
+        print_builder_setter_doc(field);
+        print_fn_header(
+            &escape_name(&field.name),
+            &[
+                "self".into(),
+                format!("{}: {}", escape_name(&field.name), setter_type(field)),
+            ],
+            &builder_type(&return_args),
+        );
+        print_struct_literal(
+            &builder_name,
+            &fields
+                .iter()
+                .map(|other_field| {
+                    if other_field.name != field.name {
+                        format!("{0}: self.{0}", escape_name(&other_field.name))
+                    } else if pointer(field).is_some() {
+                        format!("{}: {}", escape_name(&field.name), setter_value(field))
+                    } else {
+                        escape_name(&field.name)
+                    }
+                })
+                .collect::<Vec<_>>(),
+        );
+        println!("    }}");
+        println!("}}");
+    }
+
+    let optional_fields = fields
+        .iter()
+        .filter(|field| field.option_inner_type().is_some())
+        .collect::<Vec<_>>();
+    if !optional_fields.is_empty() {
+        println!();
+        print_impl_header(
+            &with_lifetime(builder_lifetime.is_some(), &params),
+            &builder_type(&params),
+        );
+        for (ind_field, field) in optional_fields.iter().enumerate() {
+            if ind_field != 0 {
+                println!();
+            }
+            print_builder_setter_doc(field);
+            print_fn_header(
+                &escape_name(&field.name),
+                &[
+                    "mut self".into(),
+                    format!("{}: {}", escape_name(&field.name), setter_type(field)),
+                ],
+                "Self",
+            );
+            println!(
+                "        self.{} = {};",
+                escape_name(&field.name),
+                setter_value(field)
+            );
+            println!("        self");
+            println!("    }}");
+        }
+        println!("}}");
+    }
+
+    println!();
+    print_impl_header(&with_lifetime(true, &[]), &builder_type(&set_args));
+    print_doc(&format!("Builds the [{name}]."), 4);
+    println!("    pub fn build(self) -> {owned_type} {{");
+    print_struct_literal(
+        name,
+        &fields
+            .iter()
+            .map(|field| format!("{0}: self.{0}", escape_name(&field.name)))
+            .collect::<Vec<_>>(),
+    );
+    println!("    }}");
+    println!("}}");
+}
+
+/// Prints the error returned when a fixed field doesn't have the expected value, naming both values.
+fn print_fixed_field_mismatch(
+    fixed_field: &FixedField,
+    json_name: &str,
+    actual: &str,
+    indent_spaces: usize,
+) {
+    let value = fixed_field.value.trim_start_matches('&');
+
+    let (message, args) = if fixed_field.is_query_version {
+        (
+            format!(
+                "expected `{}` to be {{:#x}} or {{:#x}}, got {{:#x}}",
+                json_name
+            ),
+            vec![
+                value.to_owned(),
+                format!("{value} + QUERY_VERSION_OFFSET"),
+                actual.to_owned(),
+            ],
+        )
+    } else if let Some(literal) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        (
+            format!(
+                "expected `{}` to be {}, got {{}}",
+                json_name,
+                literal.replace('{', "{{").replace('}', "}}")
+            ),
+            vec![actual.to_owned()],
+        )
+    } else {
+        (
+            format!("expected `{}` to be {{:?}}, got {{:?}}", json_name),
+            vec![value.to_owned(), actual.to_owned()],
+        )
+    };
+
+    let indent = " ".repeat(indent_spaces);
+    let args = std::iter::once(format!("\"{message}\""))
+        .chain(args)
+        .collect::<Vec<_>>();
+
+    let line = format!(
+        "{indent}return Err(serde::de::Error::custom(format!({})));",
+        args.join(", ")
+    );
+    if line.len() <= MAX_LINE_LENGTH {
+        println!("{line}");
+    } else {
+        // Like `rustfmt`, arguments following the format string are kept together when short
+        let rest = args[1..].join(", ");
+        println!("{indent}return Err(serde::de::Error::custom(format!(");
+        println!("{indent}    {},", args[0]);
+        if rest.len() <= 60 {
+            println!("{indent}    {rest},");
+        } else {
+            for arg in args[1..].iter() {
+                println!("{indent}    {arg},");
             }
         }
-        _ => {
-            dbg!(schema);
-            anyhow::bail!("Unexpected schema type when getting object fields");
+        println!("{indent})));");
+    }
+}
+
+/// Prints a struct expression as the tail of a method body. Like `rustfmt`, short ones are kept on
+/// a single line.
+fn print_struct_literal(name: &str, fields: &[String]) {
+    let body = fields.join(", ");
+    if body.len() <= 18 {
+        println!("        {name} {{ {body} }}");
+    } else {
+        println!("        {name} {{");
+        for field in fields.iter() {
+            println!("            {field},");
         }
+        println!("        }}");
     }
+}
 
-    Ok(())
+/// Prints the doc and attributes of a builder setter.
+fn print_builder_setter_doc(field: &RustField) {
+    if let Some(doc) = &field.description {
+        print_doc(doc, 4);
+    }
+    if let Some(deprecation) = &field.deprecation {
+        print_deprecated(deprecation, 4);
+    }
+    // Setters such as `is_query` take `self` by value, unlike the predicates Clippy expects
+    if field.name.starts_with("is_") {
+        println!("    #[allow(clippy::wrong_self_convention)]");
+    }
 }
 
-fn get_rust_type_for_field(schema: &Schema, specs: &Specification) -> Result<RustFieldType> {
-    match schema {
-        Schema::Ref(value) => {
-            let ref_type_name = value.name();
-            if !specs.components.schemas.contains_key(ref_type_name) {
-                anyhow::bail!("Ref target type not found: {}", ref_type_name);
-            }
+/// Prints an `impl` header, breaking lines the same way `rustfmt` does when it's too long.
+fn print_impl_header(params: &[String], self_type: &str) {
+    let params = if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", params.join(", "))
+    };
 
-            // Hard-coded special rules
-            Ok(
-                get_field_type_override(ref_type_name).unwrap_or_else(|| RustFieldType {
-                    type_name: to_starknet_rs_name(ref_type_name),
-                    serializer: None,
-                }),
-            )
+    let line = format!("impl{params} {self_type} {{");
+    if line.len() <= MAX_LINE_LENGTH {
+        println!("{line}");
+        return;
+    }
+
+    println!("impl{params}");
+    match self_type.split_once('<') {
+        Some((type_name, args)) if self_type.len() + 4 > MAX_LINE_LENGTH => {
+            println!("    {type_name}<");
+            print_generic_args(args, 8);
+            println!("    >");
         }
-        Schema::OneOf(_) => {
-            anyhow::bail!("Anonymous oneOf types should not be used for properties");
+        _ => println!("    {self_type}"),
+    }
+    println!("{{");
+}
+
+/// Prints the generic arguments of a type, given without the opening `<`, one per line.
+fn print_generic_args(args: &str, indent_spaces: usize) {
+    let indent = " ".repeat(indent_spaces);
+
+    // Only splits on top-level commas
+    let mut depth = 0;
+    let mut arg = String::new();
+    for c in args[..(args.len() - 1)].chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            _ => {}
         }
-        Schema::AllOf(_) => {
-            anyhow::bail!("Anonymous allOf types should not be used for properties");
+        if c == ',' && depth == 0 {
+            println!("{indent}{},", arg.trim());
+            arg.clear();
+        } else {
+            arg.push(c);
         }
-        Schema::Primitive(value) => match value {
-            Primitive::Array(value) => {
-                let item_type = get_rust_type_for_field(&value.items, specs)?;
-                let serializer = match item_type.serializer {
-                    Some(SerializerOverride::Serde(_)) => {
-                        todo!("Array wrapper for #[serde(with)] not implemented")
-                    }
-                    Some(SerializerOverride::SerdeAs(serializer)) => {
-                        Some(SerializerOverride::SerdeAs(format!("Vec<{serializer}>")))
-                    }
-                    None => None,
-                };
-                Ok(RustFieldType {
-                    type_name: format!("Vec<{}>", item_type.type_name),
-                    serializer,
-                })
-            }
-            Primitive::Boolean(_) => Ok(RustFieldType {
-                type_name: String::from("bool"),
-                serializer: None,
-            }),
-            Primitive::Integer(_) => Ok(RustFieldType {
-                type_name: String::from("u64"),
-                serializer: None,
-            }),
-            Primitive::Object(_) => {
-                anyhow::bail!("Anonymous object types should not be used for properties");
-            }
-            Primitive::String(value) => {
-                // Hacky solution but it's the best we can do given the specs
-                if let Some(desc) = &value.description {
-                    if desc.contains("base64") {
-                        return Ok(RustFieldType {
-                            type_name: String::from("Vec<u8>"),
-                            serializer: Some(SerializerOverride::Serde(String::from("base64"))),
-                        });
-                    }
-                }
+    }
+    println!("{indent}{},", arg.trim());
+}
 
-                Ok(RustFieldType {
-                    type_name: String::from("String"),
-                    serializer: None,
-                })
+/// Prints a method header, putting each parameter on its own line when it's too long.
+fn print_fn_header(name: &str, params: &[String], return_type: &str) {
+    let line = format!(
+        "    pub fn {}({}) -> {} {{",
+        name,
+        params.join(", "),
+        return_type
+    );
+    if line.len() > MAX_LINE_LENGTH {
+        println!("    pub fn {name}(");
+        for param in params.iter() {
+            println!("        {param},");
+        }
+        match return_type.split_once('<') {
+            Some((type_name, args)) if return_type.len() + 11 > MAX_LINE_LENGTH => {
+                println!("    ) -> {type_name}<");
+                print_generic_args(args, 8);
+                println!("    > {{");
             }
-        },
+            _ => println!("    ) -> {return_type} {{"),
+        }
+    } else {
+        println!("{line}");
     }
 }
 
-fn get_field_type_override(type_name: &str) -> Option<RustFieldType> {
-    Some(match type_name {
-        "ADDRESS" | "STORAGE_KEY" | "TXN_HASH" | "FELT" | "BLOCK_HASH" | "CHAIN_ID"
-        | "PROTOCOL_VERSION" => RustFieldType {
-            type_name: String::from("FieldElement"),
-            serializer: Some(SerializerOverride::SerdeAs(String::from("UfeHex"))),
-        },
-        "ETH_ADDRESS" => RustFieldType {
-            type_name: String::from("EthAddress"),
-            serializer: None,
-        },
-        "EXECUTION_RESULT" => RustFieldType {
-            type_name: String::from("ExecutionResult"),
-            serializer: None,
-        },
-        "BLOCK_NUMBER" => RustFieldType {
-            type_name: String::from("u64"),
-            serializer: None,
-        },
-        "NUM_AS_HEX" => RustFieldType {
-            type_name: String::from("u64"),
-            serializer: Some(SerializerOverride::SerdeAs(String::from("NumAsHex"))),
-        },
-        "SIGNATURE" => RustFieldType {
-            type_name: String::from("Vec<FieldElement>"),
-            serializer: Some(SerializerOverride::SerdeAs(String::from("Vec<UfeHex>"))),
-        },
-        "CONTRACT_ABI" => RustFieldType {
-            type_name: String::from("Vec<LegacyContractAbiEntry>"),
-            serializer: None,
-        },
-        "CONTRACT_ENTRY_POINT_LIST" => RustFieldType {
-            type_name: String::from("Vec<ContractEntryPoint>"),
-            serializer: None,
-        },
-        "LEGACY_CONTRACT_ENTRY_POINT_LIST" => RustFieldType {
-            type_name: String::from("Vec<LegacyContractEntryPoint>"),
-            serializer: None,
-        },
-        "TXN_TYPE" => RustFieldType {
-            type_name: String::from("String"),
-            serializer: None,
-        },
-        _ => return None,
-    })
+fn print_deprecated(note: &str, indent_spaces: usize) {
+    let indent = " ".repeat(indent_spaces);
+    let line = format!("{indent}#[deprecated(note = {note:?})]");
+
+    if line.len() > MAX_LINE_LENGTH {
+        println!("{indent}#[deprecated(");
+        println!("{indent}    note = {note:?}");
+        println!("{indent})]");
+    } else {
+        println!("{line}");
+    }
 }
 
 fn print_doc(doc: &str, indent_spaces: usize) {
@@ -1515,6 +4763,90 @@ fn wrap_lines(doc: &str, prefix_length: usize) -> Vec<String> {
     lines
 }
 
+/// Parses the main specification, with the methods and errors of the write specification merged
+/// into it.
+pub(crate) fn parse_merged_specs(profile: &GenerationProfile) -> Specification {
+    let mut specs: Specification =
+        serde_json::from_str(profile.raw_specs.main).expect("Failed to parse specification");
+
+    // Merge specs (we only care about write methods and errors at the moment as the write specs
+    // does not provide additional models).
+    let mut write_specs: Specification =
+        serde_json::from_str(profile.raw_specs.write).expect("Failed to parse specification");
+    specs.methods.append(&mut write_specs.methods);
+    write_specs
+        .components
+        .errors
+        .iter()
+        .for_each(|(key, value)| {
+            if let indexmap::map::Entry::Vacant(entry) =
+                specs.components.errors.entry(key.to_owned())
+            {
+                entry.insert(value.to_owned());
+            }
+        });
+
+    specs
+}
+
+/// Name of the type used for a schema in generated code, which is a built-in type for schemas with
+/// manual overrides.
+pub(crate) fn rust_type_name(schema_name: &str, options: &ProfileOptions) -> String {
+    if options
+        .pattern_newtypes
+        .iter()
+        .any(|item| item == schema_name)
+    {
+        return to_type_name(schema_name, &options.naming);
+    }
+
+    match get_field_type_override(schema_name) {
+        Some(field_type) => field_type.type_name,
+        None => to_type_name(schema_name, &options.naming),
+    }
+}
+
+/// Name of the request or response type generated for a method.
+pub(crate) fn method_type_name(method_name: &str, suffix: &str) -> String {
+    format!(
+        "{}{}",
+        to_starknet_rs_name(&camel_to_snake_case(
+            method_name.trim_start_matches("starknet_")
+        )),
+        suffix
+    )
+}
+
+/// Name of the type generated for a schema.
+pub(crate) fn to_type_name(schema_name: &str, naming: &NamingOptions) -> String {
+    to_type_identifier(&match naming.find_type(schema_name) {
+        Some(type_name) => type_name.to_owned(),
+        None => match naming.case {
+            CaseStrategy::StarknetRs => to_starknet_rs_name(schema_name),
+            CaseStrategy::PascalCase => to_pascal_case(schema_name),
+        },
+    })
+}
+
+/// Name of the field generated for a property of the schema mapped to `type_name`.
+fn to_field_name(type_name: &str, property: &str, naming: &NamingOptions) -> String {
+    match naming.find_field(type_name, property) {
+        Some(field_name) => to_identifier(field_name),
+        None => to_rust_field_name(property),
+    }
+}
+
+/// Name of the variant generated for an enum value or error of `type_name`.
+pub(crate) fn to_variant_name(type_name: &str, value: &str, naming: &NamingOptions) -> String {
+    to_type_identifier(&match naming.find_variant(type_name, value) {
+        Some(variant_name) => variant_name.to_owned(),
+        None => match naming.case {
+            CaseStrategy::StarknetRs => to_starknet_rs_name(value),
+            CaseStrategy::PascalCase => to_pascal_case(value),
+        },
+    })
+}
+
 fn to_starknet_rs_name(name: &str) -> String {
     let name = to_pascal_case(name).replace("Txn", "Transaction");
 
@@ -1545,12 +4877,20 @@ fn to_starknet_rs_name(name: &str) -> String {
 fn to_rust_field_name(name: &str) -> String {
     let all_upper_letters_regex = Regex::new("^[A-Z]+$").unwrap();
 
-    if all_upper_letters_regex.is_match(name) || name.contains('_') {
+    // Characters that can't be part of identifiers separate words the same way as underscores
+    let name = name.replace(
+        |character: char| !character.is_ascii_alphanumeric() && character != '_',
+        "_",
+    );
+
+    let field_name = if all_upper_letters_regex.is_match(&name) || name.contains('_') {
         // Already snake case
         name.to_ascii_lowercase()
     } else {
-        camel_to_snake_case(name)
-    }
+        camel_to_snake_case(&name)
+    };
+
+    to_identifier(&field_name)
 }
 
 fn to_starknet_rs_doc(doc: &str, force_period: bool) -> String {
@@ -1617,6 +4957,24 @@ fn camel_to_snake_case(name: &str) -> String {
     result
 }
 
+/// Converts Rust type names to method name suffixes, keeping digits attached (`V1` to `v1`).
+fn to_snake_case(name: &str) -> String {
+    let mut result = String::new();
+
+    for (ind, character) in name.chars().enumerate() {
+        if character.is_ascii_uppercase() {
+            if ind != 0 {
+                result.push('_');
+            }
+            result.push(character.to_ascii_lowercase());
+        } else {
+            result.push(character);
+        }
+    }
+
+    result
+}
+
 fn to_sentence_case(name: &str) -> String {
     let mut result = String::new();
 
@@ -1645,10 +5003,321 @@ fn to_sentence_case(name: &str) -> String {
     result
 }
 
-fn escape_name(name: &str) -> &str {
-    if name == "type" {
-        "r#type"
+/// Turns a name into a valid identifier. Characters that can't be used become `_`, a `_` is put in
+/// front of leading digits, and after keywords that can't be raw identifiers. Other keywords are
+/// written as raw identifiers by [escape_name].
+fn to_identifier(name: &str) -> String {
+    let mut identifier = name
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() || character == '_' {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    if identifier.is_empty() || identifier.starts_with(|character: char| character.is_ascii_digit())
+    {
+        identifier.insert(0, '_');
+    }
+    if identifier == "_" || NON_RAW_KEYWORDS.contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+
+    identifier
+}
+
+/// Same as [to_identifier], except that all keywords get a trailing `_`, as type and variant names
+/// aren't written as raw identifiers.
+fn to_type_identifier(name: &str) -> String {
+    let mut identifier = to_identifier(name);
+    if RUST_KEYWORDS.contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+
+    identifier
+}
+
+/// Writes keywords as raw identifiers, except for the ones that can't be raw and get a trailing `_`.
+fn escape_name(name: &str) -> String {
+    if NON_RAW_KEYWORDS.contains(&name) {
+        format!("{name}_")
+    } else if RUST_KEYWORDS.contains(&name) {
+        format!("r#{name}")
     } else {
-        name
+        name.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RawSpecs;
+
+    /// Whether the name can be used as an identifier in generated code.
+    fn is_valid_identifier(name: &str) -> bool {
+        if let Some(keyword) = name.strip_prefix("r#") {
+            return RUST_KEYWORDS.contains(&keyword) && !NON_RAW_KEYWORDS.contains(&keyword);
+        }
+
+        name != "_"
+            && !name.is_empty()
+            && !name.starts_with(|character: char| character.is_ascii_digit())
+            && name
+                .chars()
+                .all(|character| character.is_ascii_alphanumeric() || character == '_')
+            && !RUST_KEYWORDS.contains(&name)
+    }
+
+    #[test]
+    fn test_keyword_field_names() {
+        for keyword in RUST_KEYWORDS.iter() {
+            let escaped = escape_name(&to_identifier(keyword));
+            assert!(
+                is_valid_identifier(&escaped),
+                "{keyword} escaped as {escaped}"
+            );
+
+            if NON_RAW_KEYWORDS.contains(keyword) {
+                assert_eq!(escaped, format!("{keyword}_"));
+            } else {
+                assert_eq!(escaped, format!("r#{keyword}"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_keyword_type_names() {
+        for keyword in RUST_KEYWORDS.iter() {
+            let identifier = to_type_identifier(keyword);
+            assert!(
+                is_valid_identifier(&identifier),
+                "{keyword} became {identifier}"
+            );
+            assert_eq!(identifier, format!("{keyword}_"));
+            assert_eq!(escape_name(&identifier), identifier);
+        }
+    }
+
+    #[test]
+    fn test_escape_keywords() {
+        for keyword in RUST_KEYWORDS.iter() {
+            assert!(is_valid_identifier(&escape_name(keyword)));
+        }
+        assert_eq!(escape_name("match"), "r#match");
+        assert_eq!(escape_name("self"), "self_");
+        assert_eq!(escape_name("block_hash"), "block_hash");
+    }
+
+    #[test]
+    fn test_non_raw_keywords() {
+        for keyword in ["self", "Self", "crate", "super"] {
+            assert!(NON_RAW_KEYWORDS.contains(&keyword));
+            assert_eq!(to_identifier(keyword), format!("{keyword}_"));
+            assert_eq!(to_type_identifier(keyword), format!("{keyword}_"));
+        }
+    }
+
+    #[test]
+    fn test_invalid_characters() {
+        assert_eq!(to_identifier("0x"), "_0x");
+        assert_eq!(to_identifier("2fa"), "_2fa");
+        assert_eq!(to_identifier("max-fee"), "max_fee");
+        assert_eq!(to_identifier("-"), "__");
+        assert_eq!(to_identifier("_"), "__");
+        assert_eq!(to_identifier(""), "__");
+        assert_eq!(to_type_identifier("1Block"), "_1Block");
+        assert_eq!(to_type_identifier("L1-Handler"), "L1_Handler");
+
+        for name in ["0x", "2fa", "max-fee", "-", "_", "", "a.b", "fee$"] {
+            assert!(is_valid_identifier(&to_identifier(name)), "{name}");
+            assert!(is_valid_identifier(&to_type_identifier(name)), "{name}");
+        }
+    }
+
+    fn integer_type(schema: serde_json::Value) -> Result<String> {
+        get_rust_integer_type(&serde_json::from_value(schema)?, false)
+    }
+
+    #[test]
+    fn test_integer_types_from_bounds() {
+        let cases = [
+            (
+                serde_json::json!({ "minimum": 0, "maximum": 4294967295u64 }),
+                "u32",
+            ),
+            (serde_json::json!({ "minimum": 0 }), "u64"),
+            (
+                serde_json::json!({ "maximum": 18446744073709551615u64 }),
+                "u64",
+            ),
+            (
+                serde_json::from_str("{ \"maximum\": 18446744073709551616 }").unwrap(),
+                "u128",
+            ),
+            (
+                serde_json::from_str("{ \"maximum\": 340282366920938463463374607431768211455 }")
+                    .unwrap(),
+                "u128",
+            ),
+            (serde_json::json!({ "minimum": -1 }), "i64"),
+            (
+                serde_json::json!({ "minimum": -1, "maximum": 18446744073709551615u64 }),
+                "i128",
+            ),
+        ];
+
+        for (schema, expected) in cases {
+            assert_eq!(integer_type(schema.clone()).unwrap(), expected, "{schema}");
+        }
+
+        assert!(integer_type(serde_json::from_str("{ \"maximum\": 1e40 }").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_boxing_fixed_size_arrays() {
+        assert_eq!(value_reference("[TreeNode; 2]"), Some("TreeNode"));
+        assert_eq!(value_reference("Option<[TreeNode; 2]>"), Some("TreeNode"));
+        assert_eq!(value_reference("[TreeNode; 0]"), None);
+        assert_eq!(value_reference("Vec<TreeNode>"), None);
+
+        assert_eq!(
+            box_type("[TreeNode; 2]"),
+            "[alloc::boxed::Box<TreeNode>; 2]"
+        );
+        assert_eq!(
+            box_type("Option<[TreeNode; 2]>"),
+            "Option<[alloc::boxed::Box<TreeNode>; 2]>"
+        );
+    }
+
+    #[test]
+    fn test_bool_schema_value() {
+        let value =
+            |schema: serde_json::Value| bool_schema_value(&serde_json::from_value(schema).unwrap());
+
+        assert_eq!(
+            value(serde_json::json!({ "type": "boolean", "const": false })),
+            Some(false)
+        );
+        assert_eq!(
+            value(serde_json::json!({ "type": "boolean", "enum": [true] })),
+            Some(true)
+        );
+        assert_eq!(
+            value(serde_json::json!({ "type": "boolean", "enum": [true, false] })),
+            None
+        );
+        // Titles are prose and aren't taken as values
+        assert_eq!(
+            value(serde_json::json!({ "type": "boolean", "title": "False" })),
+            None
+        );
+    }
+
+    #[test]
+    fn test_unused_rename_keys() {
+        let naming: NamingOptions = serde_json::from_value(serde_json::json!({
+            "types": { "BLOCK_HASH": "Hash", "NOPE_SCHEMA": "Nope" },
+            "fields": { "Block.block_hash": "hash" }
+        }))
+        .unwrap();
+
+        assert_eq!(to_type_name("BLOCK_HASH", &naming), "Hash");
+        assert_eq!(to_field_name("Block", "block_hash", &naming), "hash");
+        assert!(naming.check_unused_keys().is_err());
+
+        assert_eq!(to_type_name("NOPE_SCHEMA", &naming), "Nope");
+        assert!(naming.check_unused_keys().is_ok());
+    }
+
+    #[test]
+    fn test_cow_through_borrowing_types() {
+        let mut options: serde_json::Value =
+            serde_json::from_str(include_str!("../profiles/0.4.0.json")).unwrap();
+        options["field_wrapping"]["wrapped_types"]
+            .as_array_mut()
+            .unwrap()
+            .push(
+                serde_json::json!({ "name": "FlattenedSierraClass", "fields": { "abi": "Cow" } }),
+            );
+        options["builders"] = serde_json::json!(["BroadcastedDeclareTransactionV2"]);
+
+        let profile = GenerationProfile {
+            version: SpecVersion::V0_4_0,
+            raw_specs: RawSpecs {
+                main: include_str!("../specs/0.4.0/starknet_api_openrpc.json"),
+                write: include_str!("../specs/0.4.0/starknet_write_api.json"),
+                trace: include_str!("../specs/0.4.0/starknet_trace_api_openrpc.json"),
+            },
+            options: serde_json::from_value(options).unwrap(),
+        };
+        let result = resolve_types(&parse_merged_specs(&profile), &profile.options).unwrap();
+
+        let find_struct = |name: &str| {
+            result
+                .model_types
+                .iter()
+                .chain(result.request_response_types.iter())
+                .find_map(|item| match &item.content {
+                    RustTypeKind::Struct(inner) if item.name == name => Some(inner),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        let declare = find_struct("BroadcastedDeclareTransactionV2");
+        assert!(declare.lifetime && declare.extra_ref_type && declare.builder);
+        assert_eq!(declare.conversions[0].target, "DeclareTransactionV2");
+        assert!(!declare.conversions[0].target_lifetime);
+
+        // Requests hold the manually implemented enum, which has to borrow as well
+        let request = find_struct("AddDeclareTransactionRequest");
+        assert!(request.lifetime && request.serde_as_array);
+        assert_eq!(
+            request.fields[0].type_name,
+            "BroadcastedDeclareTransaction<'a>"
+        );
+        assert!(result
+            .borrowing_manual_types
+            .contains("BROADCASTED_DECLARE_TXN"));
+    }
+
+    #[test]
+    fn test_shared_pointers_through_serde_as() {
+        let field = RustField {
+            description: None,
+            name: String::from("abi"),
+            optional: false,
+            fixed: None,
+            wrapper: Some(FieldWrapper::Arc),
+            type_name: String::from("String"),
+            serde_rename: None,
+            serde_faltten: false,
+            serializer: None,
+            deprecation: None,
+            ref_type: None,
+            fragments: vec![],
+            pattern: None,
+        };
+
+        // Serde only implements its traits for `Arc` with the `rc` feature
+        assert!(field.uses_serde_as());
+        assert_eq!(
+            field.def_lines(0, true, false, false),
+            [
+                "#[serde_as(as = \"alloc::sync::Arc<serde_with::Same>\")]",
+                "pub abi: alloc::sync::Arc<String>,"
+            ]
+        );
+        assert_eq!(field.def_lines(0, true, false, true), ["pub abi: String,"]);
+
+        let boxed = RustField {
+            wrapper: Some(FieldWrapper::Box),
+            ..field
+        };
+        assert!(!boxed.uses_serde_as());
     }
 }
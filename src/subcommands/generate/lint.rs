@@ -0,0 +1,128 @@
+use super::*;
+
+/// Words in field names suggesting integers written as hex strings on the wire.
+const HEX_NUMBER_WORDS: [&str; 3] = ["nonce", "version", "fee"];
+
+/// A type mapping that likely doesn't match how the value is encoded on the wire.
+pub struct MappingWarning {
+    pub type_name: String,
+    pub field_name: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for MappingWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.field_name {
+            Some(field_name) => write!(f, "{}.{}: {}", self.type_name, field_name, self.message),
+            None => write!(f, "{}: {}", self.type_name, self.message),
+        }
+    }
+}
+
+impl TypeResolutionResult {
+    /// Flags integers that look like they should be hex-encoded, and strings with the felt pattern
+    /// that aren't mapped to `FieldElement`. Both usually come from specs using inline schemas
+    /// where they should have referenced `NUM_AS_HEX` or `FELT`.
+    pub fn lint_mappings(
+        &self,
+        specs: &Specification,
+        options: &ProfileOptions,
+    ) -> Vec<MappingWarning> {
+        let felt_pattern = match specs.components.schemas.get("FELT") {
+            Some(Schema::Primitive(Primitive::String(value))) => value.pattern.as_deref(),
+            _ => None,
+        };
+
+        let mut warnings = vec![];
+        for rust_type in self
+            .model_types
+            .iter()
+            .chain(self.request_response_types.iter())
+        {
+            match &rust_type.content {
+                RustTypeKind::Struct(value) => {
+                    for field in value.fields.iter().filter(|field| !field.serde_faltten) {
+                        let json_name = field.serde_rename.as_ref().unwrap_or(&field.name);
+                        let warning = |message: String| MappingWarning {
+                            type_name: rust_type.name.to_owned(),
+                            field_name: Some(json_name.to_owned()),
+                            message,
+                        };
+
+                        if is_hex_number_name(json_name)
+                            && is_plain_integer(&field.type_name, field.serializer.as_ref())
+                        {
+                            warnings.push(warning(format!(
+                                "integer `{}` is not serialized as hex",
+                                field.type_name
+                            )));
+                        }
+
+                        if field.pattern.is_some()
+                            && field.pattern.as_deref() == felt_pattern
+                            && !field.type_name.contains("FieldElement")
+                        {
+                            warnings.push(warning(format!(
+                                "string with the felt pattern is mapped to `{}` instead of \
+                                 `FieldElement`",
+                                field.type_name
+                            )));
+                        }
+                    }
+                }
+                RustTypeKind::Wrapper(value) => {
+                    if is_hex_number_name(&rust_type.name)
+                        && is_plain_integer(&value.type_name, value.serializer.as_ref())
+                    {
+                        warnings.push(MappingWarning {
+                            type_name: rust_type.name.to_owned(),
+                            field_name: None,
+                            message: format!(
+                                "integer `{}` is not serialized as hex",
+                                value.type_name
+                            ),
+                        });
+                    }
+                }
+                RustTypeKind::PatternString(value) => {
+                    // Newtypes requested in the profile are deliberate
+                    let is_newtype = options
+                        .pattern_newtypes
+                        .iter()
+                        .any(|item| to_type_name(item, &options.naming) == rust_type.name);
+                    if !is_newtype && Some(value.pattern.as_str()) == felt_pattern {
+                        warnings.push(MappingWarning {
+                            type_name: rust_type.name.to_owned(),
+                            field_name: None,
+                            message: String::from(
+                                "string with the felt pattern is not mapped to `FieldElement`",
+                            ),
+                        });
+                    }
+                }
+                RustTypeKind::Enum(_) | RustTypeKind::Unit(_) | RustTypeKind::Constant(_) => {}
+            }
+        }
+
+        warnings
+    }
+}
+
+fn is_hex_number_name(name: &str) -> bool {
+    let name = camel_to_snake_case(name).to_ascii_lowercase();
+    HEX_NUMBER_WORDS.iter().any(|word| name.contains(word))
+}
+
+/// Whether the type is an integer, possibly inside an `Option` or `Vec`, that's serialized as a
+/// JSON number.
+fn is_plain_integer(type_name: &str, serializer: Option<&SerializerOverride>) -> bool {
+    let is_hex = matches!(
+        serializer,
+        Some(SerializerOverride::SerdeAs(serializer))
+            if serializer.contains("NumAsHex") || serializer.contains("UfeHex")
+    );
+
+    let inner = generic_argument(type_name, "Option").unwrap_or(type_name);
+    let inner = generic_argument(inner, "Vec").unwrap_or(inner);
+    !is_hex && is_integer_type(inner)
+}
@@ -0,0 +1,503 @@
+use super::*;
+
+/// Values tried in order for string schemas with a pattern, covering felts, hex numbers and
+/// Ethereum addresses.
+const PATTERN_SAMPLES: [&str; 5] = [
+    "0x1",
+    "0x0000000000000000000000000000000000000001",
+    "0x0000000000000000000000000000000000000000000000000000000000000001",
+    "1",
+    "a",
+];
+
+/// Strings checked against pattern newtypes on top of [PATTERN_SAMPLES], including invalid felts
+/// and base64 strings.
+const PATTERN_TEST_INPUTS: [&str; 8] = [
+    "",
+    "0x",
+    "0x0",
+    "0x01",
+    "0xg",
+    "0x1000000000000000000000000000000000000000000000000000000000000000",
+    "AAAA",
+    "AA==",
+];
+
+impl TypeResolutionResult {
+    /// Prints tests checking that `Ref` types requested in the profile serialize the same way as
+    /// the types they borrow from, that enums with unknown variants round-trip any string, and
+    /// that pattern newtypes accept the same strings as their patterns. Instances are deserialized
+    /// from samples built from the schemas.
+    pub fn render_tests_stdout(&self, specs: &Specification, options: &ProfileOptions) {
+        let structs = self
+            .model_types
+            .iter()
+            .filter_map(|item| match &item.content {
+                RustTypeKind::Struct(inner) => Some((item.name.as_str(), inner)),
+                _ => None,
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut tests = vec![];
+        for name in options.ref_types.iter() {
+            let schema_name = specs
+                .components
+                .schemas
+                .keys()
+                .find(|schema_name| &to_type_name(schema_name, &options.naming) == name);
+
+            let sample = match schema_name {
+                Some(schema_name) => {
+                    let mut sampler = Sampler {
+                        specs,
+                        options,
+                        structs: &structs,
+                        stack: vec![],
+                    };
+                    sampler.sample_ref(schema_name)
+                }
+                None => Err(anyhow::anyhow!("schema not found")),
+            };
+
+            match sample {
+                Ok(sample) => tests.push((name, sample)),
+                Err(err) => eprintln!("Unable to generate Ref type test for {name}: {err}"),
+            }
+        }
+
+        let unknown_enums = self
+            .model_types
+            .iter()
+            .filter_map(|item| match &item.content {
+                RustTypeKind::Enum(inner) if inner.unknown_variant => Some((&item.name, inner)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let pattern_strings = self
+            .model_types
+            .iter()
+            .filter_map(|item| match &item.content {
+                RustTypeKind::PatternString(inner) => Some((&item.name, inner)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if tests.is_empty() && unknown_enums.is_empty() && pattern_strings.is_empty() {
+            return;
+        }
+
+        println!();
+        println!("#[cfg(test)]");
+        println!("mod tests {{");
+        println!("    use super::*;");
+
+        for (name, sample) in tests.iter() {
+            let sample = serde_json::to_string(sample).expect("Unable to serialize sample");
+            let hashes = if sample.contains("\"#") { "##" } else { "#" };
+
+            println!();
+            println!("    #[test]");
+            println!("    fn test_{}_ref_serialization() {{", to_snake_case(name));
+            let value = format!("serde_json::from_str(r{hashes}\"{sample}\"{hashes}).unwrap()");
+            let line = format!("        let value: {name} = {value};");
+            if line.len() <= MAX_LINE_LENGTH {
+                println!("{line}");
+            } else {
+                println!("        let value: {name} =");
+                println!("            {value};");
+            }
+            println!();
+            println!("        assert_eq!(");
+            println!("            serde_json::to_value(&value).unwrap(),");
+            println!("            serde_json::to_value({name}Ref::from(&value)).unwrap()");
+            println!("        );");
+            println!("    }}");
+        }
+
+        for (name, rust_enum) in unknown_enums.iter() {
+            rust_enum.render_unknown_variant_test_stdout(name);
+        }
+
+        for (name, pattern_string) in pattern_strings.iter() {
+            pattern_string.render_test_stdout(name);
+        }
+
+        println!("}}");
+    }
+}
+
+impl RustEnum {
+    /// Prints a test checking that a known value maps to its variant and that any other value is
+    /// kept as is in `Unknown`.
+    fn render_unknown_variant_test_stdout(&self, name: &str) {
+        let serde_names = self
+            .variants
+            .iter()
+            .map(|variant| variant.serde_name.as_ref().unwrap_or(&variant.name))
+            .collect::<Vec<_>>();
+
+        let mut unknown_value = String::from("UNKNOWN_VALUE");
+        while serde_names.contains(&&unknown_value) {
+            unknown_value.push('_');
+        }
+
+        println!();
+        println!("    #[test]");
+        println!("    fn test_{}_unknown_variant() {{", to_snake_case(name));
+
+        if let (Some(variant), Some(serde_name)) = (self.variants.first(), serde_names.first()) {
+            let json = serde_json::to_string(serde_name).expect("Unable to serialize variant");
+            print_test_value(name, &format!("serde_json::from_str({json:?}).unwrap()"));
+            print_test_assert_eq("value", &format!("{name}::{}", variant.name));
+            print_test_assert_eq(
+                "serde_json::to_string(&value).unwrap()",
+                &format!("{json:?}"),
+            );
+            println!();
+        }
+
+        let json = format!("\"{unknown_value}\"");
+        print_test_value(name, &format!("serde_json::from_str({json:?}).unwrap()"));
+        print_test_assert_eq(
+            "value",
+            &format!("{name}::Unknown(String::from({unknown_value:?}))"),
+        );
+        print_test_assert_eq(
+            "serde_json::to_string(&value).unwrap()",
+            &format!("{json:?}"),
+        );
+        println!("    }}");
+    }
+}
+
+impl RustPatternString {
+    /// Prints a test checking that the generated matcher accepts strings when, and only when, the
+    /// `regex` crate finds that they match the pattern.
+    fn render_test_stdout(&self, name: &str) {
+        let regex = Regex::new(&self.pattern).expect("Pattern checked when resolving types");
+
+        println!();
+        println!("    #[test]");
+        println!("    fn test_{}_pattern() {{", to_snake_case(name));
+        for input in PATTERN_SAMPLES.iter().chain(PATTERN_TEST_INPUTS.iter()) {
+            let negation = if regex.is_match(input) { "" } else { "!" };
+            let call = format!("{negation}{name}::is_match({input:?})");
+            // Same as the `fn_call_width` of rustfmt
+            if call.len() <= 60 {
+                println!("        assert!({call});");
+            } else {
+                println!("        assert!({negation}{name}::is_match(");
+                println!("            {input:?}");
+                println!("        ));");
+            }
+        }
+        println!("    }}");
+    }
+}
+
+/// Prints the `value` binding of a test, wrapped the way rustfmt does if it's too long.
+fn print_test_value(type_name: &str, expr: &str) {
+    let line = format!("        let value: {type_name} = {expr};");
+    if line.len() <= MAX_LINE_LENGTH {
+        println!("{line}");
+    } else {
+        println!("        let value: {type_name} =");
+        println!("            {expr};");
+    }
+}
+
+/// Prints an `assert_eq!` of a test, wrapped the way rustfmt does if it's too long.
+fn print_test_assert_eq(left: &str, right: &str) {
+    let line = format!("        assert_eq!({left}, {right});");
+    if line.len() <= MAX_LINE_LENGTH {
+        println!("{line}");
+    } else {
+        println!("        assert_eq!(");
+        println!("            {left},");
+        println!("            {right}");
+        println!("        );");
+    }
+}
+
+/// Builds JSON values accepted by the schemas they're sampled from.
+struct Sampler<'a> {
+    specs: &'a Specification,
+    options: &'a ProfileOptions,
+    structs: &'a HashMap<&'a str, &'a RustStruct>,
+    /// Schemas being sampled, to stop at recursive references.
+    stack: Vec<String>,
+}
+
+impl<'a> Sampler<'a> {
+    fn sample_ref(&mut self, name: &str) -> Result<serde_json::Value> {
+        if self.stack.iter().any(|item| item == name) {
+            anyhow::bail!("{name} is recursive");
+        }
+        let schema = match self.specs.components.schemas.get(name) {
+            Some(schema) => schema,
+            None => anyhow::bail!("schema {name} not found"),
+        };
+
+        self.stack.push(name.to_owned());
+        let sample = self.sample(schema);
+        self.stack.pop();
+        let mut sample = sample?;
+
+        // Fixed values from the profile aren't in the specs
+        if let (Some(rust_struct), serde_json::Value::Object(object)) = (
+            self.structs
+                .get(to_type_name(name, &self.options.naming).as_str()),
+            &mut sample,
+        ) {
+            for field in rust_struct.fields.iter() {
+                if let Some(value) = fixed_sample(field) {
+                    let key = field.serde_rename.as_ref().unwrap_or(&field.name);
+                    object.insert(key.to_owned(), value);
+                }
+            }
+        }
+
+        Ok(sample)
+    }
+
+    fn sample(&mut self, schema: &Schema) -> Result<serde_json::Value> {
+        if let Some(example) = schema
+            .annotations()
+            .examples
+            .as_ref()
+            .and_then(|examples| examples.first())
+        {
+            return Ok(example.to_owned());
+        }
+
+        match schema {
+            Schema::Ref(reference) => self.sample_ref(reference.name()),
+            Schema::OneOf(value) => self.sample_any(&value.one_of),
+            Schema::AnyOf(value) => self.sample_any(&value.any_of),
+            Schema::AllOf(value) => {
+                let mut object = serde_json::Map::new();
+                for item in value.all_of.iter() {
+                    match self.sample(item)? {
+                        serde_json::Value::Object(item) => object.extend(item),
+                        _ => anyhow::bail!("allOf with non-object schemas"),
+                    }
+                }
+                Ok(serde_json::Value::Object(object))
+            }
+            Schema::Not(_) => anyhow::bail!("not schemas can't be sampled"),
+            Schema::Primitive(Primitive::Array(value)) => {
+                let len = value.min_items.unwrap_or(1);
+                match self.sample(&value.items) {
+                    Ok(item) => Ok(serde_json::Value::Array(vec![item; len as usize])),
+                    // Empty arrays end recursion
+                    Err(_) if len == 0 => Ok(serde_json::Value::Array(vec![])),
+                    Err(err) => Err(err),
+                }
+            }
+            Schema::Primitive(Primitive::Boolean(value)) => {
+                Ok(serde_json::Value::Bool(value.r#const.unwrap_or(true)))
+            }
+            Schema::Primitive(Primitive::Integer(value)) => {
+                let bound = |bound: &Option<serde_json::Number>| {
+                    bound.as_ref().and_then(|bound| bound.as_f64())
+                };
+                Ok(match (value.r#const, &value.minimum, &value.maximum) {
+                    (Some(value), _, _) => value.into(),
+                    (_, Some(minimum), _) if bound(&value.minimum) > Some(1.0) => {
+                        minimum.to_owned().into()
+                    }
+                    (_, _, Some(maximum)) if bound(&value.maximum) < Some(1.0) => {
+                        maximum.to_owned().into()
+                    }
+                    _ => 1.into(),
+                })
+            }
+            Schema::Primitive(Primitive::Number(value)) => Ok(1f64
+                .clamp(
+                    value.minimum.unwrap_or(f64::MIN),
+                    value.maximum.unwrap_or(f64::MAX),
+                )
+                .into()),
+            Schema::Primitive(Primitive::Null(_)) => Ok(serde_json::Value::Null),
+            Schema::Primitive(Primitive::Object(value)) => {
+                let mut object = serde_json::Map::new();
+                for (name, property) in value.properties.iter() {
+                    let required = value
+                        .required
+                        .as_ref()
+                        .is_some_and(|required| required.contains(name));
+
+                    // Optional properties are included so that they're covered too, unless they
+                    // can't be sampled
+                    match self.sample(property) {
+                        Ok(sample) => {
+                            object.insert(name.to_owned(), sample);
+                        }
+                        Err(err) if required => return Err(err),
+                        Err(_) => {}
+                    }
+                }
+                Ok(serde_json::Value::Object(object))
+            }
+            Schema::Primitive(Primitive::String(value)) => {
+                if let Some(value) = &value.r#const {
+                    return Ok(value.to_owned().into());
+                }
+                if let Some(value) = value.r#enum.as_ref().and_then(|values| values.first()) {
+                    return Ok(value.to_owned().into());
+                }
+                if let Some(pattern) = &value.pattern {
+                    let regex = Regex::new(pattern)?;
+                    return match PATTERN_SAMPLES.iter().find(|item| regex.is_match(item)) {
+                        Some(sample) => Ok((*sample).into()),
+                        None => anyhow::bail!("no sample matches pattern {pattern}"),
+                    };
+                }
+                if value
+                    .description
+                    .as_ref()
+                    .is_some_and(|desc| desc.contains("base64"))
+                {
+                    return Ok("AA==".into());
+                }
+
+                Ok("a".into())
+            }
+        }
+    }
+
+    /// Samples the first option that can be sampled.
+    fn sample_any(&mut self, schemas: &[Schema]) -> Result<serde_json::Value> {
+        let mut last_err = anyhow::anyhow!("no options to sample");
+        for schema in schemas.iter() {
+            match self.sample(schema) {
+                Ok(sample) => return Ok(sample),
+                Err(err) => last_err = err,
+            }
+        }
+        Err(last_err)
+    }
+}
+
+/// JSON value of a fixed field with a value from the profile, as the field serializes it.
+fn fixed_sample(field: &RustField) -> Option<serde_json::Value> {
+    let fixed = field.fixed.as_ref()?;
+    let value = fixed.value.trim_start_matches('&');
+
+    if let Some(felt) = felt_from_expr(value) {
+        let digits = felt
+            .iter()
+            .rev()
+            .map(|limb| format!("{limb:016x}"))
+            .collect::<String>();
+        let digits = digits.trim_start_matches('0');
+        return Some(format!("0x{}", if digits.is_empty() { "0" } else { digits }).into());
+    }
+
+    if let Ok(number) = value.parse::<u64>() {
+        let is_hex = matches!(
+            &field.serializer,
+            Some(SerializerOverride::SerdeAs(serializer)) if serializer.contains("NumAsHex")
+        );
+        return Some(if is_hex {
+            format!("{number:#x}").into()
+        } else {
+            number.into()
+        });
+    }
+
+    serde_json::from_str::<String>(value).ok().map(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(schemas: serde_json::Value, name: &str) -> Result<serde_json::Value> {
+        let specs: Specification = serde_json::from_value(serde_json::json!({
+            "openrpc": "1.0.0",
+            "info": { "version": "0.1.0", "title": "Test", "license": {} },
+            "servers": [],
+            "methods": [],
+            "components": {
+                "contentDescriptors": {},
+                "schemas": schemas,
+                "errors": {}
+            }
+        }))?;
+        let options: ProfileOptions = serde_json::from_value(serde_json::json!({
+            "flatten_options": "All",
+            "ignore_types": [],
+            "fixed_field_types": { "fixed_field_types": [] },
+            "field_wrapping": { "wrapped_types": [] }
+        }))?;
+
+        Sampler {
+            specs: &specs,
+            options: &options,
+            structs: &HashMap::new(),
+            stack: vec![],
+        }
+        .sample_ref(name)
+    }
+
+    #[test]
+    fn test_sample_matches_patterns() {
+        let value = sample(
+            serde_json::json!({
+                "FELT": { "type": "string", "pattern": "^0x(0|[a-fA-F1-9]{1}[a-fA-F0-9]{0,62})$" },
+                "ETH_ADDRESS": { "type": "string", "pattern": "^0x[a-fA-F0-9]{40}$" },
+                "ITEM": {
+                    "type": "object",
+                    "properties": {
+                        "hash": { "$ref": "#/components/schemas/FELT" },
+                        "address": { "$ref": "#/components/schemas/ETH_ADDRESS" },
+                        "kind": { "type": "string", "enum": ["FIRST", "SECOND"] }
+                    },
+                    "required": ["hash"]
+                }
+            }),
+            "ITEM",
+        )
+        .unwrap();
+
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "hash": "0x1",
+                "address": "0x0000000000000000000000000000000000000001",
+                "kind": "FIRST"
+            })
+        );
+    }
+
+    #[test]
+    fn test_sample_skips_optional_recursion() {
+        let schemas = serde_json::json!({
+            "NODE": {
+                "type": "object",
+                "properties": {
+                    "value": { "type": "integer", "minimum": 0 },
+                    "next": { "$ref": "#/components/schemas/NODE" }
+                },
+                "required": ["value"]
+            }
+        });
+        assert_eq!(
+            sample(schemas, "NODE").unwrap(),
+            serde_json::json!({ "value": 1 })
+        );
+
+        let schemas = serde_json::json!({
+            "NODE": {
+                "type": "object",
+                "properties": {
+                    "next": { "$ref": "#/components/schemas/NODE" }
+                },
+                "required": ["next"]
+            }
+        });
+        assert!(sample(schemas, "NODE").is_err());
+    }
+}
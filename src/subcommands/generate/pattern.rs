@@ -0,0 +1,448 @@
+use std::collections::BTreeSet;
+
+use regex_syntax::{
+    hir::{Class, Hir, HirKind, Look},
+    utf8::Utf8Sequences,
+};
+
+use super::*;
+
+/// Limit on the number of DFA states, as some patterns blow up when made deterministic.
+const MAX_DFA_STATES: usize = 1024;
+
+/// Deterministic automaton over UTF-8 bytes telling whether a pattern matches anywhere in a string,
+/// like `Regex::is_match`. Generated code runs it instead of depending on a regex engine, which
+/// keeps it `no_std` friendly.
+#[derive(Debug, Clone)]
+pub struct PatternDfa {
+    /// Transitions of each state as byte ranges and target states. State 0 is the start state, and
+    /// bytes missing from the ranges reject the input.
+    transitions: Vec<Vec<(u8, u8, usize)>>,
+    /// Whether each state matches when the input ends there.
+    accepting: Vec<bool>,
+}
+
+/// Thompson NFA, with the only assertions supported being the start and end of the input.
+enum NfaState {
+    Range(u8, u8, usize),
+    Split(Vec<usize>),
+    Start(usize),
+    End(usize),
+    Match,
+}
+
+struct Nfa {
+    states: Vec<NfaState>,
+}
+
+impl PatternDfa {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let hir = regex_syntax::Parser::new().parse(pattern)?;
+
+        let mut nfa = Nfa { states: vec![] };
+        let match_state = nfa.push(NfaState::Match);
+        let nfa_start = nfa.compile(&hir, match_state)?;
+
+        // A state reached after a match has been found anywhere in the input, as later bytes
+        // don't matter then
+        let mut matched = None;
+
+        let mut sets = vec![nfa.closure([nfa_start], true)];
+        let mut transitions = vec![];
+        let mut accepting = vec![];
+        while transitions.len() < sets.len() {
+            let id = transitions.len();
+            let set = sets[id].to_owned();
+
+            if set.contains(&match_state) {
+                matched = matched.or(Some(id));
+                transitions.push(vec![(u8::MIN, u8::MAX, id)]);
+                accepting.push(true);
+                continue;
+            }
+
+            let mut state_transitions: Vec<(u8, u8, usize)> = vec![];
+            for byte in u8::MIN..=u8::MAX {
+                let next = set.iter().filter_map(|item| match nfa.states[*item] {
+                    NfaState::Range(start, end, next) if (start..=end).contains(&byte) => {
+                        Some(next)
+                    }
+                    _ => None,
+                });
+                // The pattern can start matching at any position
+                let next_set = nfa.closure(next.chain(std::iter::once(nfa_start)), false);
+                if next_set.is_empty() {
+                    continue;
+                }
+
+                let target = match next_set.contains(&match_state).then_some(matched).flatten() {
+                    Some(target) => target,
+                    None => match sets.iter().position(|item| item == &next_set) {
+                        Some(target) => target,
+                        None => {
+                            if sets.len() == MAX_DFA_STATES {
+                                anyhow::bail!("Pattern is too complex: {pattern}");
+                            }
+                            if next_set.contains(&match_state) {
+                                matched = Some(sets.len());
+                            }
+                            sets.push(next_set);
+                            sets.len() - 1
+                        }
+                    },
+                };
+
+                match state_transitions.last_mut() {
+                    Some((_, end, last_target)) if *end + 1 == byte && *last_target == target => {
+                        *end = byte;
+                    }
+                    _ => state_transitions.push((byte, byte, target)),
+                }
+            }
+
+            transitions.push(state_transitions);
+            accepting.push(nfa.matches_at_end(&set, id == 0, match_state));
+        }
+
+        Ok(Self {
+            transitions,
+            accepting,
+        })
+    }
+
+    /// Whether the pattern can match anything at all.
+    pub fn is_satisfiable(&self) -> bool {
+        self.accepting.iter().any(|item| *item)
+    }
+
+    /// Prints the body of a function taking a `value: &str` and returning whether it matches.
+    pub fn render_stdout(&self, indent: usize) {
+        let indent = " ".repeat(indent);
+
+        if self.transitions.iter().all(|item| item.is_empty()) {
+            println!("{indent}value.is_empty()");
+            return;
+        }
+
+        println!("{indent}let mut state = 0;");
+        println!("{indent}for byte in value.bytes() {{");
+        println!("{indent}    state = match (state, byte) {{");
+
+        // Arms of consecutive states stepping to the next one on the same bytes, as in
+        // repetitions, are merged into a single arm
+        let mut arms: Vec<(usize, usize, Vec<String>, Option<usize>)> = vec![];
+        for (state, state_transitions) in self.transitions.iter().enumerate() {
+            let mut targets = state_transitions
+                .iter()
+                .map(|(_, _, target)| *target)
+                .collect::<Vec<_>>();
+            targets.sort();
+            targets.dedup();
+
+            for target in targets.into_iter() {
+                let ranges = state_transitions
+                    .iter()
+                    .filter(|(_, _, item)| *item == target)
+                    .map(|(start, end, _)| byte_range_pattern(*start, *end))
+                    .collect::<Vec<_>>();
+
+                match arms.iter_mut().find(|(_, end, arm_ranges, arm_target)| {
+                    *end + 1 == state
+                        && target == state + 1
+                        && arm_target.is_none()
+                        && *arm_ranges == ranges
+                }) {
+                    Some((_, end, _, _)) => *end = state,
+                    None if target == state + 1 => arms.push((state, state, ranges, None)),
+                    None => arms.push((state, state, ranges, Some(target))),
+                }
+            }
+        }
+
+        let arm_indent = format!("{indent}        ");
+        for (start, end, ranges, target) in arms.iter() {
+            let states = if start == end {
+                start.to_string()
+            } else {
+                format!("{start}..={end}")
+            };
+            let target = match target {
+                Some(target) => target.to_string(),
+                None if start == end => (start + 1).to_string(),
+                None => String::from("state + 1"),
+            };
+
+            let line = format!(
+                "{arm_indent}({states}, {}) => {target},",
+                ranges.join(" | ")
+            );
+            if line.len() <= MAX_LINE_LENGTH {
+                println!("{line}");
+            } else {
+                for (ind, range) in ranges.iter().enumerate() {
+                    println!(
+                        "{arm_indent}{}({states}, {range}){}",
+                        if ind == 0 { "" } else { "| " },
+                        if ind == ranges.len() - 1 {
+                            format!(" => {target},")
+                        } else {
+                            String::new()
+                        }
+                    );
+                }
+            }
+        }
+
+        println!("{arm_indent}_ => return false,");
+        println!("{indent}    }};");
+        println!("{indent}}}");
+
+        let mut accepting_ranges: Vec<(usize, usize)> = vec![];
+        for (state, _) in self.accepting.iter().enumerate().filter(|(_, item)| **item) {
+            match accepting_ranges.last_mut() {
+                Some((_, end)) if *end + 1 == state => *end = state,
+                _ => accepting_ranges.push((state, state)),
+            }
+        }
+        println!(
+            "{indent}matches!(state, {})",
+            accepting_ranges
+                .iter()
+                .map(|(start, end)| if start == end {
+                    start.to_string()
+                } else {
+                    format!("{start}..={end}")
+                })
+                .collect::<Vec<_>>()
+                .join(" | ")
+        );
+    }
+
+    #[cfg(test)]
+    fn is_match(&self, value: &str) -> bool {
+        let mut state = 0;
+        for byte in value.bytes() {
+            match self.transitions[state]
+                .iter()
+                .find(|(start, end, _)| (*start..=*end).contains(&byte))
+            {
+                Some((_, _, target)) => state = *target,
+                None => return false,
+            }
+        }
+        self.accepting[state]
+    }
+}
+
+impl Nfa {
+    fn push(&mut self, state: NfaState) -> usize {
+        self.states.push(state);
+        self.states.len() - 1
+    }
+
+    /// Adds states matching `hir` followed by `next`, returning the first one.
+    fn compile(&mut self, hir: &Hir, next: usize) -> Result<usize> {
+        Ok(match hir.kind() {
+            HirKind::Empty => next,
+            HirKind::Literal(literal) => literal.0.iter().rev().fold(next, |next, byte| {
+                self.push(NfaState::Range(*byte, *byte, next))
+            }),
+            HirKind::Class(Class::Bytes(class)) => {
+                let branches = class
+                    .ranges()
+                    .iter()
+                    .map(|range| self.push(NfaState::Range(range.start(), range.end(), next)))
+                    .collect();
+                self.push(NfaState::Split(branches))
+            }
+            HirKind::Class(Class::Unicode(class)) => {
+                let mut branches = vec![];
+                for range in class.ranges().iter() {
+                    for sequence in Utf8Sequences::new(range.start(), range.end()) {
+                        branches.push(
+                            sequence.as_slice().iter().rev().fold(next, |next, range| {
+                                self.push(NfaState::Range(range.start, range.end, next))
+                            }),
+                        );
+                    }
+                }
+                self.push(NfaState::Split(branches))
+            }
+            HirKind::Look(Look::Start) => self.push(NfaState::Start(next)),
+            HirKind::Look(Look::End) => self.push(NfaState::End(next)),
+            HirKind::Look(look) => anyhow::bail!("Unsupported assertion in pattern: {look:?}"),
+            HirKind::Repetition(repetition) => {
+                let mut first = match repetition.max {
+                    Some(max) => {
+                        let mut first = next;
+                        for _ in repetition.min..max {
+                            let item = self.compile(&repetition.sub, first)?;
+                            first = self.push(NfaState::Split(vec![item, first]));
+                        }
+                        first
+                    }
+                    None => {
+                        let first = self.push(NfaState::Split(vec![]));
+                        let item = self.compile(&repetition.sub, first)?;
+                        self.states[first] = NfaState::Split(vec![item, next]);
+                        first
+                    }
+                };
+                for _ in 0..repetition.min {
+                    first = self.compile(&repetition.sub, first)?;
+                }
+                first
+            }
+            HirKind::Capture(capture) => self.compile(&capture.sub, next)?,
+            HirKind::Concat(items) => {
+                let mut first = next;
+                for item in items.iter().rev() {
+                    first = self.compile(item, first)?;
+                }
+                first
+            }
+            HirKind::Alternation(items) => {
+                let branches = items
+                    .iter()
+                    .map(|item| self.compile(item, next))
+                    .collect::<Result<_>>()?;
+                self.push(NfaState::Split(branches))
+            }
+        })
+    }
+
+    /// States reachable from `states` without consuming input, leaving out the ones only used to
+    /// get to other states. End assertions are kept, as the end of the input isn't known yet.
+    fn closure(&self, states: impl IntoIterator<Item = usize>, at_start: bool) -> BTreeSet<usize> {
+        let mut visited = HashSet::new();
+        let mut stack = states.into_iter().collect::<Vec<_>>();
+        let mut closure = BTreeSet::new();
+
+        while let Some(state) = stack.pop() {
+            if !visited.insert(state) {
+                continue;
+            }
+
+            match &self.states[state] {
+                NfaState::Range(..) | NfaState::End(_) | NfaState::Match => {
+                    closure.insert(state);
+                }
+                NfaState::Split(branches) => stack.extend(branches.iter().rev()),
+                NfaState::Start(next) => {
+                    if at_start {
+                        stack.push(*next);
+                    }
+                }
+            }
+        }
+
+        closure
+    }
+
+    /// Whether a match is found when the input ends in `states`.
+    fn matches_at_end(&self, states: &BTreeSet<usize>, at_start: bool, match_state: usize) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = states.iter().copied().collect::<Vec<_>>();
+
+        while let Some(state) = stack.pop() {
+            if state == match_state {
+                return true;
+            }
+            if !visited.insert(state) {
+                continue;
+            }
+
+            match &self.states[state] {
+                NfaState::Split(branches) => stack.extend(branches.iter()),
+                NfaState::End(next) => stack.push(*next),
+                NfaState::Start(next) if at_start => stack.push(*next),
+                _ => {}
+            }
+        }
+
+        false
+    }
+}
+
+/// Pattern matching the bytes from `start` to `end`, using byte literals for printable ASCII.
+fn byte_range_pattern(start: u8, end: u8) -> String {
+    let byte = |value: u8| {
+        if value.is_ascii_graphic() && value != b'\'' && value != b'\\' {
+            format!("b'{}'", value as char)
+        } else {
+            format!("{value:#04x}")
+        }
+    };
+
+    if start == end {
+        byte(start)
+    } else {
+        format!("{}..={}", byte(start), byte(end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dfa_matches_like_regex() {
+        let patterns = [
+            "^0x(0|[a-fA-F1-9]{1}[a-fA-F0-9]{0,62})$",
+            "^0x0[0-7]{1}[a-fA-F0-9]{0,62}$",
+            "^0x[a-fA-F0-9]+$",
+            "^0x[a-fA-F0-9]{40}$",
+            "^(?:[A-Za-z0-9+/]{4})*(?:[A-Za-z0-9+/]{3}=|[A-Za-z0-9+/]{2}==)?$",
+            "ab+c",
+            "^a|b$",
+            "x*",
+            "^$",
+            "[^a]é",
+            "a^",
+        ];
+        let inputs = [
+            "",
+            "0x0",
+            "0x1",
+            "0x01",
+            "0x07ff",
+            "0x08",
+            "0xG",
+            "0x0000000000000000000000000000000000000001",
+            "0x800000000000011000000000000000000000000000000000000000000000001",
+            "0x0800000000000011000000000000000000000000000000000000000000000001",
+            "AAAA",
+            "AAA=",
+            "AA==",
+            "A===",
+            "abbc",
+            "zabcz",
+            "ac",
+            "ab",
+            "cb",
+            "aé",
+            "bé",
+            "éé",
+        ];
+
+        for pattern in patterns {
+            let regex = Regex::new(pattern).unwrap();
+            let dfa = PatternDfa::new(pattern).unwrap();
+            for input in inputs {
+                assert_eq!(
+                    dfa.is_match(input),
+                    regex.is_match(input),
+                    "{pattern} {input:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_unsupported_patterns() {
+        assert!(PatternDfa::new(r"\bfelt\b").is_err());
+        assert!(PatternDfa::new("(?m)^felt$").is_err());
+        assert!(PatternDfa::new("[a").is_err());
+        assert!(!PatternDfa::new("a^").unwrap().is_satisfiable());
+    }
+}
@@ -0,0 +1,493 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+const INDENT: &str = "  ";
+
+/// Field and enum value numbers assigned so far, which must never change across spec upgrades.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProtoLock {
+    #[serde(default)]
+    messages: IndexMap<String, ProtoLockEntry>,
+    #[serde(default)]
+    enums: IndexMap<String, ProtoLockEntry>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProtoLockEntry {
+    numbers: IndexMap<String, u32>,
+    /// Numbers of removed fields or values, kept so that they're never reused for anything else.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    reserved: IndexMap<String, u32>,
+}
+
+/// Types used in messages that need definitions or imports of their own.
+#[derive(Default)]
+struct ProtoContext {
+    /// Item types of nested lists, which are wrapped into messages.
+    lists: BTreeSet<String>,
+    imports: BTreeSet<&'static str>,
+}
+
+/// A message field, or a member of a `oneof`.
+struct ProtoField {
+    description: Option<String>,
+    name: String,
+    /// Label and type, like `repeated bytes`.
+    field_type: String,
+}
+
+enum ProtoItem {
+    Message {
+        name: String,
+        fields: Vec<ProtoField>,
+        /// Fields grouped into a `oneof` named `value`.
+        one_of: bool,
+    },
+    Enum {
+        name: String,
+        values: Vec<ProtoField>,
+    },
+}
+
+impl TypeResolutionResult {
+    /// Prints proto3 messages for the resolved types, numbering fields as recorded in the lock,
+    /// which is updated with the numbers of new fields.
+    pub fn render_proto_stdout(
+        &self,
+        specs: &Specification,
+        options: &ProfileOptions,
+        version: SpecVersion,
+        lock: &mut ProtoLock,
+    ) {
+        let resolution_context = ResolutionContext::new(specs, options);
+        let mut context = ProtoContext::default();
+
+        let mut manual_types = options.ignore_types.clone();
+        for name in specs.components.schemas.keys() {
+            if let Some(field_type) = resolution_context.field_type_override(name) {
+                if proto_type(&field_type.type_name, &mut ProtoContext::default())
+                    == field_type.type_name
+                {
+                    manual_types.push(name.to_owned());
+                }
+            }
+        }
+
+        let mut items = vec![];
+        for rust_type in self
+            .model_types
+            .iter()
+            .chain(self.request_response_types.iter())
+        {
+            let name = rust_type.name.to_owned();
+            let description = match (&rust_type.title, &rust_type.description) {
+                (Some(title), Some(description)) => Some(format!("{title}\n\n{description}")),
+                (title, description) => title.to_owned().or(description.to_owned()),
+            };
+
+            let item = match &rust_type.content {
+                RustTypeKind::Struct(value) => ProtoItem::Message {
+                    name,
+                    fields: value.proto_fields(&mut context),
+                    one_of: false,
+                },
+                RustTypeKind::Enum(value) if value.is_untagged() => ProtoItem::Message {
+                    name,
+                    fields: value
+                        .variants
+                        .iter()
+                        .map(|variant| ProtoField {
+                            description: variant.description.to_owned(),
+                            name: to_snake_case(&variant.name),
+                            field_type: match &variant.payload {
+                                Some(payload) => proto_type(&payload.type_name, &mut context),
+                                None => {
+                                    context.imports.insert("google/protobuf/empty.proto");
+                                    String::from("google.protobuf.Empty")
+                                }
+                            },
+                        })
+                        .collect(),
+                    one_of: true,
+                },
+                RustTypeKind::Enum(value) => ProtoItem::Enum {
+                    values: value
+                        .variants
+                        .iter()
+                        .map(|variant| ProtoField {
+                            description: variant.description.to_owned(),
+                            name: to_snake_case(&variant.name).to_ascii_uppercase(),
+                            field_type: String::new(),
+                        })
+                        .collect(),
+                    name,
+                },
+                RustTypeKind::Wrapper(value) => ProtoItem::Message {
+                    name,
+                    fields: vec![ProtoField {
+                        description: None,
+                        name: String::from("value"),
+                        field_type: proto_field_type(&value.type_name, &mut context),
+                    }],
+                    one_of: false,
+                },
+                RustTypeKind::Unit(_) => ProtoItem::Message {
+                    name,
+                    fields: vec![],
+                    one_of: false,
+                },
+                RustTypeKind::PatternString(_) => ProtoItem::Message {
+                    name,
+                    fields: vec![ProtoField {
+                        description: None,
+                        name: String::from("value"),
+                        field_type: String::from("string"),
+                    }],
+                    one_of: false,
+                },
+                // Protobuf has no constants
+                RustTypeKind::Constant(_) => continue,
+            };
+            items.push((description, item));
+        }
+
+        // Enums implemented by hand in starknet-rs become `oneof`s of the referenced schemas
+        for name in self.not_implemented.iter() {
+            let variants = match specs.components.schemas.get(name) {
+                Some(Schema::OneOf(value)) => value
+                    .one_of
+                    .iter()
+                    .map(|item| match item {
+                        Schema::Ref(reference) => Some(reference.name()),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>(),
+                _ => None,
+            };
+            let Some(variants) = variants else {
+                manual_types.push(name.to_owned());
+                continue;
+            };
+
+            let schema = &specs.components.schemas[name];
+            items.push((
+                schema
+                    .title()
+                    .or(schema.description())
+                    .map(|doc| to_starknet_rs_doc(doc, true)),
+                ProtoItem::Message {
+                    name: to_type_name(name, &options.naming),
+                    fields: variants
+                        .into_iter()
+                        .map(|variant| {
+                            let type_name = match resolution_context.field_type_override(variant) {
+                                Some(field_type) => field_type.type_name,
+                                None => to_type_name(variant, &options.naming),
+                            };
+                            ProtoField {
+                                description: None,
+                                name: to_snake_case(&to_type_name(variant, &options.naming)),
+                                field_type: proto_type(&type_name, &mut context),
+                            }
+                        })
+                        .collect(),
+                    one_of: true,
+                },
+            ));
+        }
+
+        println!("syntax = \"proto3\";");
+        println!();
+        println!(
+            "package starknet.rpc.{};",
+            format!("{version:?}").to_ascii_lowercase()
+        );
+
+        if !context.imports.is_empty() {
+            println!();
+            for import in context.imports.iter() {
+                println!("import \"{import}\";");
+            }
+        }
+        println!();
+
+        if !manual_types.is_empty() {
+            println!("// These types are not generated. Define them manually:");
+            for type_name in manual_types.iter() {
+                println!("// - `{}`", to_type_name(type_name, &options.naming));
+            }
+            println!();
+        }
+
+        for (ind, (description, item)) in items.iter().enumerate() {
+            if let Some(description) = description {
+                print_proto_doc(description, "");
+            }
+            item.render_stdout(lock);
+
+            if ind != items.len() - 1 || !context.lists.is_empty() {
+                println!();
+            }
+        }
+
+        for (ind, item_type) in context.lists.iter().enumerate() {
+            println!("message {} {{", list_message_name(item_type));
+            println!("{INDENT}repeated {item_type} items = 1;");
+            println!("}}");
+
+            if ind != context.lists.len() - 1 {
+                println!();
+            }
+        }
+    }
+}
+
+impl RustStruct {
+    fn proto_fields(&self, context: &mut ProtoContext) -> Vec<ProtoField> {
+        let mut fields = self
+            .fields
+            .iter()
+            // Fixed fields are implied by the message type
+            .filter(|field| field.fixed.is_none())
+            .map(|field| ProtoField {
+                description: field.description.to_owned(),
+                name: field.name.to_owned(),
+                field_type: proto_field_type(&field.type_name, context),
+            })
+            .collect::<Vec<_>>();
+
+        if self.fields.iter().any(|field| {
+            field
+                .fixed
+                .as_ref()
+                .is_some_and(|fixed| fixed.is_query_version)
+        }) {
+            fields.push(ProtoField {
+                description: Some(String::from(
+                    "If set to `true`, uses a query-only transaction version that's invalid for \
+                    execution",
+                )),
+                name: String::from("is_query"),
+                field_type: String::from("bool"),
+            });
+        }
+
+        fields
+    }
+}
+
+impl ProtoItem {
+    fn render_stdout(&self, lock: &mut ProtoLock) {
+        match self {
+            Self::Message {
+                name,
+                fields,
+                one_of,
+            } => {
+                let entry = lock.messages.entry(name.to_owned()).or_default();
+                let numbers = entry.assign(fields, 1);
+
+                println!("message {name} {{");
+                entry.print_reserved(INDENT);
+                let indent = if *one_of {
+                    println!("{INDENT}oneof value {{");
+                    format!("{INDENT}{INDENT}")
+                } else {
+                    String::from(INDENT)
+                };
+                for (field, number) in fields.iter().zip(numbers) {
+                    if let Some(description) = &field.description {
+                        print_proto_doc(description, &indent);
+                    }
+                    println!("{indent}{} {} = {number};", field.field_type, field.name);
+                }
+                if *one_of {
+                    println!("{INDENT}}}");
+                }
+                println!("}}");
+            }
+            Self::Enum { name, values } => {
+                let entry = lock.enums.entry(name.to_owned()).or_default();
+                let numbers = entry.assign(values, 1);
+
+                // Enum values are scoped to the package instead of the enum
+                let prefix = to_snake_case(name).to_ascii_uppercase();
+
+                println!("enum {name} {{");
+                entry.print_reserved(INDENT);
+                println!("{INDENT}{prefix}_UNSPECIFIED = 0;");
+                for (value, number) in values.iter().zip(numbers) {
+                    if let Some(description) = &value.description {
+                        print_proto_doc(description, INDENT);
+                    }
+                    println!("{INDENT}{prefix}_{} = {number};", value.name);
+                }
+                println!("}}");
+            }
+        }
+    }
+}
+
+impl ProtoLockEntry {
+    /// Numbers of the fields, in order. Locked numbers are kept, and fields that are gone get their
+    /// numbers reserved.
+    fn assign(&mut self, fields: &[ProtoField], first: u32) -> Vec<u32> {
+        let mut numbers = vec![];
+        for field in fields.iter() {
+            let number = match (
+                self.numbers.get(&field.name),
+                self.reserved.remove(&field.name),
+            ) {
+                (Some(number), _) => *number,
+                (None, Some(number)) => number,
+                (None, None) => self
+                    .numbers
+                    .values()
+                    .chain(self.reserved.values())
+                    .map(|number| number + 1)
+                    .max()
+                    .unwrap_or(first),
+            };
+            self.numbers.insert(field.name.to_owned(), number);
+            numbers.push(number);
+        }
+
+        let removed = self
+            .numbers
+            .keys()
+            .filter(|name| !fields.iter().any(|field| field.name == **name))
+            .cloned()
+            .collect::<Vec<_>>();
+        for name in removed.into_iter() {
+            if let Some(number) = self.numbers.shift_remove(&name) {
+                self.reserved.insert(name, number);
+            }
+        }
+
+        numbers
+    }
+
+    fn print_reserved(&self, indent: &str) {
+        if self.reserved.is_empty() {
+            return;
+        }
+
+        println!(
+            "{indent}reserved {};",
+            self.reserved
+                .values()
+                .map(|number| number.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        println!(
+            "{indent}reserved {};",
+            self.reserved
+                .keys()
+                .map(|name| format!("{name:?}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
+
+/// Label and type of a message field. Lists nested in other lists or in maps are wrapped into
+/// messages, as they can't be repeated directly.
+fn proto_field_type(type_name: &str, context: &mut ProtoContext) -> String {
+    if let Some(inner) = generic_argument(type_name, "Option") {
+        let field_type = proto_field_type(inner, context);
+        return if field_type.starts_with("repeated ") || field_type.starts_with("map<") {
+            field_type
+        } else {
+            format!("optional {field_type}")
+        };
+    }
+    if type_name != "Vec<u8>" {
+        if let Some(inner) = generic_argument(type_name, "Vec") {
+            return format!("repeated {}", proto_type(inner, context));
+        }
+    }
+    if let Some(value_type) = generic_argument(type_name, "alloc::collections::BTreeMap")
+        .and_then(|inner| inner.strip_prefix("String, "))
+    {
+        return format!("map<string, {}>", proto_type(value_type, context));
+    }
+
+    proto_type(type_name, context)
+}
+
+/// Type of a field without labels, as used in `oneof`s and as map values.
+fn proto_type(type_name: &str, context: &mut ProtoContext) -> String {
+    if let Some(inner) = [
+        "Option",
+        "OwnedPtr",
+        "alloc::boxed::Box",
+        "alloc::sync::Arc",
+        "alloc::rc::Rc",
+    ]
+    .iter()
+    .find_map(|wrapper| generic_argument(type_name, wrapper))
+    {
+        return proto_type(inner, context);
+    }
+    if type_name == "Vec<u8>" {
+        return String::from("bytes");
+    }
+    if let Some(inner) = generic_argument(type_name, "Vec") {
+        let item_type = proto_type(inner, context);
+        let list_name = list_message_name(&item_type);
+        context.lists.insert(item_type);
+        return list_name;
+    }
+    if generic_argument(type_name, "alloc::collections::BTreeMap").is_some()
+        || type_name == "serde_json::Value"
+    {
+        context.imports.insert("google/protobuf/struct.proto");
+        return String::from("google.protobuf.Struct");
+    }
+
+    match type_name
+        .trim_end_matches("<'a>")
+        .trim_start_matches("core::num::NonZero")
+    {
+        "FieldElement" | "EthAddress" | "u128" | "U128" | "i128" | "I128" => String::from("bytes"),
+        "String" => String::from("string"),
+        "bool" => String::from("bool"),
+        "f64" => String::from("double"),
+        "u8" | "u16" | "u32" | "U8" | "U16" | "U32" => String::from("uint32"),
+        "u64" | "U64" => String::from("uint64"),
+        "i8" | "i16" | "i32" => String::from("int32"),
+        "i64" => String::from("int64"),
+        "()" => {
+            context.imports.insert("google/protobuf/empty.proto");
+            String::from("google.protobuf.Empty")
+        }
+        type_name => type_name.to_owned(),
+    }
+}
+
+/// Name of the message wrapping lists of an item type, like `BytesList`.
+fn list_message_name(item_type: &str) -> String {
+    let item_name = item_type.rsplit('.').next().unwrap_or(item_type);
+    match item_name.split_at_checked(1) {
+        Some((first, rest)) => format!("{}{rest}List", first.to_ascii_uppercase()),
+        None => String::from("List"),
+    }
+}
+
+fn print_proto_doc(doc: &str, indent: &str) {
+    let prefix = format!("{indent}// ");
+    for (ind, paragraph) in doc.split("\n\n").enumerate() {
+        if ind != 0 {
+            println!("{indent}//");
+        }
+        for line in wrap_lines(&paragraph.replace('\n', " "), prefix.len()) {
+            println!("{prefix}{line}");
+        }
+    }
+}
@@ -0,0 +1,604 @@
+use super::*;
+
+const INDENT: &str = "  ";
+
+impl TypeResolutionResult {
+    /// Prints TypeScript declarations describing the JSON representation of the resolved types.
+    pub fn render_typescript_stdout(&self, specs: &Specification, options: &ProfileOptions) {
+        let mut context = ResolutionContext::new(specs, options);
+        let query_version_offset =
+            parse_felt(&options.query_version.offset).expect("Invalid query version offset");
+
+        let mut manual_names = options.ignore_types.clone();
+
+        // Types hand-written in starknet-rs instead of mapping to built-in types
+        for name in specs.components.schemas.keys() {
+            if let Some(field_type) = context.field_type_override(name) {
+                if ts_type(&field_type.type_name, field_type.serializer.as_ref())
+                    == field_type.type_name
+                {
+                    manual_names.push(name.to_owned());
+                }
+            }
+        }
+        manual_names.extend(self.not_implemented.iter().cloned());
+
+        // Starknet-rs implements these types by hand, which TypeScript has no counterpart for
+        let mut manual_types = vec![];
+        let mut undeclared_types = vec![];
+        for name in manual_names.iter() {
+            match resolve_manual_type(specs, name, &mut context) {
+                Ok(manual_type) => manual_types.push(manual_type),
+                Err(_) => undeclared_types.push(name),
+            }
+        }
+        for hoisted_type in context.hoisted_types.into_iter() {
+            if !self
+                .model_types
+                .iter()
+                .chain(manual_types.iter())
+                .any(|item| item.name == hoisted_type.name)
+            {
+                manual_types.push(hoisted_type);
+            }
+        }
+
+        if !undeclared_types.is_empty() {
+            println!("// These types are not generated. Declare them manually:");
+            for type_name in undeclared_types.iter() {
+                println!("// - `{}`", to_type_name(type_name, &options.naming));
+            }
+            println!();
+        }
+
+        let types = self
+            .model_types
+            .iter()
+            .chain(self.request_response_types.iter())
+            .chain(manual_types.iter())
+            .collect::<Vec<_>>();
+        let tags = ts_type_tags(&types, query_version_offset);
+
+        for (ind, rust_type) in types.iter().enumerate() {
+            print_ts_doc(
+                rust_type.title.as_deref(),
+                rust_type.description.as_deref(),
+                rust_type.deprecation.as_deref(),
+                "",
+            );
+
+            match &rust_type.content {
+                RustTypeKind::Struct(value) => {
+                    value.render_typescript_stdout(&rust_type.name, query_version_offset)
+                }
+                RustTypeKind::Enum(value) => value.render_typescript_stdout(&rust_type.name, &tags),
+                RustTypeKind::Wrapper(value) => println!(
+                    "export type {} = {};",
+                    rust_type.name,
+                    ts_type(&value.type_name, value.serializer.as_ref())
+                ),
+                RustTypeKind::Unit(value) => println!(
+                    "export type {} = {};",
+                    rust_type.name,
+                    if value.serde_as_array { "[]" } else { "null" }
+                ),
+                RustTypeKind::PatternString(_) => {
+                    println!("export type {} = string;", rust_type.name)
+                }
+                RustTypeKind::Constant(value) => println!(
+                    "export declare const {}: {};",
+                    camel_to_snake_case(&rust_type.name)
+                        .trim_start_matches('_')
+                        .to_ascii_uppercase(),
+                    ts_literal(&value.value, &value.type_name, None)
+                        .unwrap_or_else(|| ts_type(&value.type_name, None))
+                ),
+            }
+
+            if ind != types.len() - 1 {
+                println!();
+            }
+        }
+    }
+}
+
+impl RustStruct {
+    pub fn render_typescript_stdout(&self, name: &str, query_version_offset: [u64; 4]) {
+        let (flattened, fields): (Vec<_>, Vec<_>) =
+            self.fields.iter().partition(|field| field.serde_faltten);
+
+        if flattened.is_empty() {
+            println!("export interface {name} {{");
+        } else {
+            println!("export type {name} = {{");
+        }
+
+        for field in fields.iter() {
+            print_ts_doc(
+                None,
+                field.description.as_deref(),
+                field.deprecation.as_deref(),
+                INDENT,
+            );
+
+            let type_name = match &field.fixed {
+                Some(fixed) => fixed.ts_type(field, query_version_offset),
+                None => ts_type(
+                    field.option_inner_type().unwrap_or(&field.type_name),
+                    field.serializer.as_ref(),
+                ),
+            };
+            println!(
+                "{INDENT}{}{}: {};",
+                ts_property_name(field.serde_rename.as_ref().unwrap_or(&field.name)),
+                if field.optional { "?" } else { "" },
+                type_name
+            );
+        }
+
+        if flattened.is_empty() {
+            println!("}}");
+        } else {
+            print!("}}");
+            for field in flattened.iter() {
+                print!(
+                    " & {}",
+                    ts_type(&field.type_name, field.serializer.as_ref())
+                );
+            }
+            println!(";");
+        }
+    }
+}
+
+impl RustEnum {
+    pub fn render_typescript_stdout(&self, name: &str, tags: &HashMap<String, Vec<String>>) {
+        // Members told apart by their `type` field get it spelled out, the way internally tagged
+        // enums are written in starknet-rs.
+        let is_discriminated = self.is_untagged()
+            && self
+                .ts_payload_tags(tags)
+                .map(|member_tags| {
+                    member_tags
+                        .into_iter()
+                        .flatten()
+                        .collect::<HashSet<_>>()
+                        .len()
+                        > 1
+                })
+                .unwrap_or(false);
+
+        let mut variants = self
+            .variants
+            .iter()
+            .map(|variant| {
+                if self.is_untagged() {
+                    match (&variant.payload, variant.bool_value) {
+                        (Some(_), Some(value)) => value.to_string(),
+                        (Some(payload), None) => {
+                            let type_name =
+                                ts_type(&payload.type_name, payload.serializer.as_ref());
+                            match tags.get(&type_name).map(|tags| tags.as_slice()) {
+                                Some([tag]) if is_discriminated => {
+                                    format!("({{ type: {tag} }} & {type_name})")
+                                }
+                                _ => type_name,
+                            }
+                        }
+                        (None, _) => String::from("null"),
+                    }
+                } else {
+                    format!("{:?}", variant.serde_name.as_ref().unwrap_or(&variant.name))
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Any other string is accepted, while keeping known values suggested by editors
+        if self.unknown_variant {
+            variants.push(String::from("(string & {})"));
+        }
+
+        print_ts_union(name, &variants);
+    }
+
+    /// Values of the `type` field of each payload, if all payloads have a fixed one.
+    fn ts_payload_tags<'a>(
+        &self,
+        tags: &'a HashMap<String, Vec<String>>,
+    ) -> Option<Vec<&'a Vec<String>>> {
+        self.variants
+            .iter()
+            .map(|variant| {
+                let payload = variant.payload.as_ref()?;
+                tags.get(&ts_type(&payload.type_name, payload.serializer.as_ref()))
+            })
+            .collect()
+    }
+}
+
+impl FixedField {
+    /// Literal type of the value written for the field, falling back to the field type for values
+    /// that can't be evaluated.
+    fn ts_type(&self, field: &RustField, query_version_offset: [u64; 4]) -> String {
+        let type_name = field.option_inner_type().unwrap_or(&field.type_name);
+
+        if self.is_query_version {
+            // Query-only versions are accepted in place of the transaction version
+            if let Some(version) = felt_from_expr(self.value.trim_start_matches('&')) {
+                return format!(
+                    "{:?} | {:?}",
+                    felt_to_hex(version),
+                    felt_to_hex(felt_add(version, query_version_offset))
+                );
+            }
+        }
+
+        ts_literal(&self.value, type_name, field.serializer.as_ref())
+            .unwrap_or_else(|| ts_type(type_name, field.serializer.as_ref()))
+    }
+}
+
+/// Resolves a schema starknet-rs declares by hand into a type to be declared in TypeScript.
+fn resolve_manual_type(
+    specs: &Specification,
+    name: &str,
+    context: &mut ResolutionContext,
+) -> Result<RustType> {
+    let schema = specs
+        .components
+        .schemas
+        .get(name)
+        .ok_or_else(|| anyhow::anyhow!("Schema not found: {name}"))?;
+    let rusty_name = to_type_name(name, &context.options.naming);
+
+    let mut content = match schema {
+        // Members are named without looking them up, as some point to schemas the spec misses
+        Schema::OneOf(value)
+            if value
+                .one_of
+                .iter()
+                .all(|item| matches!(item, Schema::Ref(_))) =>
+        {
+            RustTypeKind::Enum(RustEnum {
+                is_error: false,
+                lifetime: false,
+                unknown_variant: false,
+                variants: value
+                    .one_of
+                    .iter()
+                    .filter_map(|item| match item {
+                        Schema::Ref(reference) => Some(reference.name()),
+                        _ => None,
+                    })
+                    .map(|ref_name| {
+                        let payload = context.field_type_override(ref_name).unwrap_or_else(|| {
+                            RustFieldType {
+                                type_name: to_type_name(ref_name, &context.options.naming),
+                                serializer: None,
+                            }
+                        });
+                        RustVariant {
+                            description: None,
+                            name: payload.type_name.clone(),
+                            serde_name: None,
+                            error_text: None,
+                            payload: Some(payload),
+                            bool_value: None,
+                        }
+                    })
+                    .collect(),
+            })
+        }
+        _ => match schema_to_rust_type_kind(specs, schema, context, &rusty_name)? {
+            Some(content) => content,
+            None => method_result_to_rust_type_kind(specs, schema, context, &rusty_name)?,
+        },
+    };
+
+    if let RustTypeKind::Struct(inner) = &mut content {
+        for field in inner.fields.iter_mut() {
+            if let Some(fixed) = context
+                .options
+                .fixed_field_types
+                .find_fixed_field(&rusty_name, &field.name)
+            {
+                field.fixed = Some(fixed);
+            }
+        }
+    }
+
+    Ok(RustType {
+        title: schema.title().map(|value| to_starknet_rs_doc(value, true)),
+        description: schema
+            .description()
+            .or_else(|| schema.summary())
+            .map(|value| to_starknet_rs_doc(value, true)),
+        deprecation: context
+            .spec_deprecation(schema.annotations().deprecated, schema.description()),
+        extra_attributes: ExtraAttributes::default(),
+        name: rusty_name,
+        content,
+    })
+}
+
+/// Literal values of the fixed `type` field of each type, going through the members of unions.
+fn ts_type_tags(
+    types: &[&RustType],
+    query_version_offset: [u64; 4],
+) -> HashMap<String, Vec<String>> {
+    let mut tags = HashMap::new();
+
+    for rust_type in types.iter() {
+        if let RustTypeKind::Struct(value) = &rust_type.content {
+            let tag = value.fields.iter().find_map(|field| {
+                match (
+                    field.serde_rename.as_ref().unwrap_or(&field.name).as_str(),
+                    &field.fixed,
+                ) {
+                    ("type", Some(fixed)) => Some(fixed.ts_type(field, query_version_offset)),
+                    _ => None,
+                }
+            });
+            if let Some(tag) = tag {
+                tags.insert(rust_type.name.clone(), vec![tag]);
+            }
+        }
+    }
+
+    // Unions nest other unions, so their tags are collected until none is left to resolve
+    loop {
+        let mut resolved = false;
+        for rust_type in types.iter() {
+            if let RustTypeKind::Enum(value) = &rust_type.content {
+                if tags.contains_key(&rust_type.name) {
+                    continue;
+                }
+                if let Some(member_tags) = value.ts_payload_tags(&tags) {
+                    let mut union_tags: Vec<String> = vec![];
+                    for tag in member_tags.into_iter().flatten() {
+                        if !union_tags.contains(tag) {
+                            union_tags.push(tag.to_owned());
+                        }
+                    }
+                    tags.insert(rust_type.name.clone(), union_tags);
+                    resolved = true;
+                }
+            }
+        }
+
+        if !resolved {
+            return tags;
+        }
+    }
+}
+
+/// Maps a Rust type to the TypeScript type of its JSON representation, taking into account
+/// serializers writing numbers as hex strings.
+fn ts_type(type_name: &str, serializer: Option<&SerializerOverride>) -> String {
+    let is_hex = matches!(
+        serializer,
+        Some(SerializerOverride::SerdeAs(serializer))
+            if serializer.contains("UfeHex") || serializer.contains("NumAsHex")
+    );
+    let is_base64 = matches!(
+        serializer,
+        Some(SerializerOverride::Serde(serializer)) if serializer == "base64"
+    );
+
+    if let Some(inner) = generic_argument(type_name, "Option") {
+        return format!("{} | null", ts_type(inner, serializer));
+    }
+    if let Some(inner) = [
+        "OwnedPtr",
+        "alloc::boxed::Box",
+        "alloc::sync::Arc",
+        "alloc::rc::Rc",
+    ]
+    .iter()
+    .find_map(|wrapper| generic_argument(type_name, wrapper))
+    {
+        return ts_type(inner, serializer);
+    }
+    if let Some(inner) = generic_argument(type_name, "Vec") {
+        if is_base64 {
+            return String::from("string");
+        }
+
+        let item_type = ts_type(inner, serializer);
+        return if item_type.contains(' ') {
+            format!("({item_type})[]")
+        } else {
+            format!("{item_type}[]")
+        };
+    }
+    if let Some(value_type) = generic_argument(type_name, "alloc::collections::BTreeMap")
+        .and_then(|inner| inner.strip_prefix("String, "))
+    {
+        return format!("Record<string, {}>", ts_type(value_type, serializer));
+    }
+
+    match type_name.trim_end_matches("<'a>") {
+        "FieldElement" | "EthAddress" | "String" | "&str" => String::from("string"),
+        "bool" => String::from("boolean"),
+        "f64" => String::from("number"),
+        "()" => String::from("null"),
+        "serde_json::Value" => String::from("unknown"),
+        integer if is_integer_type(integer) => {
+            if is_hex {
+                String::from("string")
+            } else {
+                String::from("number")
+            }
+        }
+        type_name => type_name.to_owned(),
+    }
+}
+
+/// Literal type for a constant Rust expression, if it can be evaluated.
+fn ts_literal(
+    value: &str,
+    type_name: &str,
+    serializer: Option<&SerializerOverride>,
+) -> Option<String> {
+    let value = value.trim_start_matches('&');
+    if value.starts_with('"') {
+        // Escapes in `Debug` output of ASCII strings are valid in JSON
+        return serde_json::from_str::<String>(value)
+            .ok()
+            .map(|value| format!("{value:?}"));
+    }
+
+    let number =
+        felt_from_expr(value).or_else(|| value.parse::<u64>().ok().map(|n| [n, 0, 0, 0]))?;
+    if ts_type(type_name, serializer) == "string" {
+        Some(format!("{:?}", felt_to_hex(number)))
+    } else {
+        match number {
+            [number, 0, 0, 0] => Some(number.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Prints a union type on one line if it fits, with one member per line otherwise.
+fn print_ts_union(name: &str, variants: &[String]) {
+    let line = format!("export type {name} = {};", variants.join(" | "));
+    if line.len() <= MAX_LINE_LENGTH {
+        println!("{line}");
+    } else {
+        println!("export type {name} =");
+        for (ind, variant) in variants.iter().enumerate() {
+            println!(
+                "{INDENT}| {variant}{}",
+                if ind == variants.len() - 1 { ";" } else { "" }
+            );
+        }
+    }
+}
+
+fn print_ts_doc(
+    title: Option<&str>,
+    description: Option<&str>,
+    deprecation: Option<&str>,
+    indent: &str,
+) {
+    let mut paragraphs = title.into_iter().chain(description).collect::<Vec<_>>();
+    let deprecation = deprecation.map(|value| format!("@deprecated {value}"));
+    paragraphs.extend(deprecation.as_deref());
+
+    if paragraphs.is_empty() {
+        return;
+    }
+
+    if let [paragraph] = paragraphs.as_slice() {
+        let line = format!("{indent}/** {paragraph} */");
+        if line.len() <= MAX_LINE_LENGTH {
+            println!("{line}");
+            return;
+        }
+    }
+
+    let prefix = format!("{indent} * ");
+    println!("{indent}/**");
+    for (ind, paragraph) in paragraphs.iter().enumerate() {
+        if ind != 0 {
+            println!("{indent} *");
+        }
+        for line in wrap_lines(paragraph, prefix.len()) {
+            println!("{prefix}{line}");
+        }
+    }
+    println!("{indent} */");
+}
+
+fn ts_property_name(name: &str) -> String {
+    let is_identifier = name
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_' || first == '$')
+        && name
+            .chars()
+            .all(|item| item.is_ascii_alphanumeric() || item == '_' || item == '$');
+
+    if is_identifier {
+        name.to_owned()
+    } else {
+        format!("{name:?}")
+    }
+}
+
+fn felt_to_hex(value: [u64; 4]) -> String {
+    let digits = value
+        .iter()
+        .rev()
+        .map(|limb| format!("{limb:016x}"))
+        .collect::<String>();
+    match digits.trim_start_matches('0') {
+        "" => String::from("0x0"),
+        digits => format!("0x{digits}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::RawSpecs;
+
+    #[test]
+    fn test_manual_types_and_tagged_unions() {
+        let profile = GenerationProfile {
+            version: SpecVersion::V0_4_0,
+            raw_specs: RawSpecs {
+                main: include_str!("../../specs/0.4.0/starknet_api_openrpc.json"),
+                write: include_str!("../../specs/0.4.0/starknet_write_api.json"),
+                trace: include_str!("../../specs/0.4.0/starknet_trace_api_openrpc.json"),
+            },
+            options: serde_json::from_str(include_str!("../../profiles/0.4.0.json")).unwrap(),
+        };
+        let specs = parse_merged_specs(&profile);
+        let result = resolve_types(&specs, &profile.options).unwrap();
+        let mut context = ResolutionContext::new(&specs, &profile.options);
+
+        // Hand-written in starknet-rs, but declared from the spec here
+        let manual_types = result
+            .not_implemented
+            .iter()
+            .chain(std::iter::once(&String::from("EXECUTION_RESULT")))
+            .map(|name| resolve_manual_type(&specs, name, &mut context).unwrap())
+            .collect::<Vec<_>>();
+        let find_enum = |name: &str| {
+            manual_types
+                .iter()
+                .find_map(|item| match &item.content {
+                    RustTypeKind::Enum(inner) if item.name == name => Some(inner),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        assert!(manual_types
+            .iter()
+            .any(|item| item.name == "ExecutionResult"
+                && matches!(item.content, RustTypeKind::Struct(_))));
+
+        let types = result
+            .model_types
+            .iter()
+            .chain(manual_types.iter())
+            .collect::<Vec<_>>();
+        let tags = ts_type_tags(&types, [0; 4]);
+
+        assert_eq!(tags["InvokeTransactionReceipt"], [r#""INVOKE""#]);
+        assert_eq!(tags["DeclareTransaction"], [r#""DECLARE""#]);
+        assert_eq!(tags["Transaction"].len(), 5);
+        assert!(!tags.contains_key("BlockId"));
+
+        assert_eq!(
+            find_enum("TransactionReceipt")
+                .ts_payload_tags(&tags)
+                .map(|member_tags| member_tags.len()),
+            Some(6)
+        );
+        assert!(find_enum("BlockId").ts_payload_tags(&tags).is_none());
+    }
+}
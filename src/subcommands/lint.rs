@@ -0,0 +1,368 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use indexmap::IndexMap;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{spec::*, GenerationProfile, SpecVersion};
+
+#[derive(Debug, Parser)]
+pub struct Lint {
+    #[clap(
+        long,
+        required_unless_present = "file",
+        conflicts_with = "file",
+        help = "Version of the bundled specification to lint"
+    )]
+    spec: Option<SpecVersion>,
+    #[clap(
+        long,
+        help = "OpenRPC document to lint instead of a bundled specification"
+    )]
+    file: Vec<PathBuf>,
+    #[clap(long, help = "JSON file setting the severity of each rule")]
+    config: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Rule {
+    /// Documents that can't be parsed as a specification.
+    InvalidDocument,
+    /// Methods, params, results, schemas and properties without a description or title.
+    MissingDescription,
+    /// Schema names that aren't `SCREAMING_SNAKE_CASE`.
+    SchemaNaming,
+    /// Property names that aren't `snake_case`.
+    PropertyNaming,
+    /// Objects with properties but no `required` list, making every field optional.
+    MissingRequired,
+    /// Distinct errors sharing the same code.
+    DuplicateErrorCode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Severity {
+    Off,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LintConfig {
+    /// Severities overriding the defaults of the rules.
+    #[serde(default)]
+    rules: HashMap<Rule, Severity>,
+}
+
+#[derive(Debug, Serialize)]
+struct Diagnostic {
+    rule: Rule,
+    severity: Severity,
+    document: String,
+    /// JSON pointer to the offending item in the document.
+    location: String,
+    message: String,
+}
+
+/// Collects the diagnostics of a document, dropping the ones of disabled rules.
+struct Linter<'a> {
+    config: &'a LintConfig,
+    document: String,
+    diagnostics: Vec<Diagnostic>,
+    schema_name_regex: Regex,
+    property_name_regex: Regex,
+}
+
+impl Lint {
+    pub(crate) fn run(self, profiles: &[GenerationProfile]) -> Result<()> {
+        let config = match &self.config {
+            Some(path) => serde_json::from_str(
+                &std::fs::read_to_string(path)
+                    .with_context(|| format!("Unable to read lint config: {path:?}"))?,
+            )
+            .with_context(|| format!("Invalid lint config: {path:?}"))?,
+            None => LintConfig::default(),
+        };
+
+        let documents = match self.spec {
+            Some(version) => {
+                let profile = profiles
+                    .iter()
+                    .find(|profile| profile.version == version)
+                    .expect("Unable to find profile");
+                vec![
+                    (
+                        String::from("starknet_api_openrpc.json"),
+                        profile.raw_specs.main.to_owned(),
+                    ),
+                    (
+                        String::from("starknet_write_api.json"),
+                        profile.raw_specs.write.to_owned(),
+                    ),
+                    (
+                        String::from("starknet_trace_api_openrpc.json"),
+                        profile.raw_specs.trace.to_owned(),
+                    ),
+                ]
+            }
+            None => self
+                .file
+                .iter()
+                .map(|path| {
+                    Ok((
+                        path.display().to_string(),
+                        std::fs::read_to_string(path)
+                            .with_context(|| format!("Unable to read document: {path:?}"))?,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?,
+        };
+
+        let mut diagnostics = vec![];
+        // Error codes are shared across documents, keyed by code, with the error names and where
+        // they're defined
+        let mut error_codes: IndexMap<i64, IndexMap<String, (String, String)>> = IndexMap::new();
+
+        for (document, raw_specs) in documents.into_iter() {
+            let mut linter = Linter::new(&config, document.clone());
+
+            match serde_json::from_str::<Specification>(&raw_specs) {
+                Ok(specs) => {
+                    linter.lint_specification(&specs);
+
+                    for (name, error) in specs.components.errors.iter() {
+                        if let ErrorType::Error(error) = error {
+                            error_codes.entry(error.code).or_default().insert(
+                                name.to_owned(),
+                                (
+                                    document.clone(),
+                                    format!("#/components/errors/{}", escape_pointer(name)),
+                                ),
+                            );
+                        }
+                    }
+                }
+                Err(err) => linter.report(
+                    Rule::InvalidDocument,
+                    String::from("#"),
+                    format!("Unable to parse specification: {err}"),
+                ),
+            }
+
+            diagnostics.append(&mut linter.diagnostics);
+        }
+
+        for (code, errors) in error_codes.iter().filter(|(_, errors)| errors.len() > 1) {
+            let names = errors.keys().cloned().collect::<Vec<_>>().join(", ");
+            for (document, location) in errors.values() {
+                let mut linter = Linter::new(&config, document.to_owned());
+                linter.report(
+                    Rule::DuplicateErrorCode,
+                    location.to_owned(),
+                    format!("Error code {code} is used by {names}"),
+                );
+                diagnostics.append(&mut linter.diagnostics);
+            }
+        }
+
+        println!("{}", serde_json::to_string_pretty(&diagnostics)?);
+
+        let error_count = diagnostics
+            .iter()
+            .filter(|item| item.severity == Severity::Error)
+            .count();
+        eprintln!(
+            "{} error(s), {} warning(s)",
+            error_count,
+            diagnostics.len() - error_count
+        );
+
+        // Lint errors are an expected outcome rather than a failure of the command
+        if error_count > 0 {
+            std::process::exit(1);
+        }
+
+        Ok(())
+    }
+}
+
+impl Rule {
+    fn default_severity(&self) -> Severity {
+        match self {
+            Self::InvalidDocument | Self::DuplicateErrorCode => Severity::Error,
+            Self::MissingDescription
+            | Self::SchemaNaming
+            | Self::PropertyNaming
+            | Self::MissingRequired => Severity::Warning,
+        }
+    }
+}
+
+impl<'a> Linter<'a> {
+    fn new(config: &'a LintConfig, document: String) -> Self {
+        Self {
+            config,
+            document,
+            diagnostics: vec![],
+            schema_name_regex: Regex::new("^[A-Z][A-Z0-9]*(_[A-Z0-9]+)*$").unwrap(),
+            property_name_regex: Regex::new("^[a-z][a-z0-9]*(_[a-z0-9]+)*$").unwrap(),
+        }
+    }
+
+    fn report(&mut self, rule: Rule, location: String, message: String) {
+        let severity = self
+            .config
+            .rules
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| rule.default_severity());
+
+        if severity != Severity::Off {
+            self.diagnostics.push(Diagnostic {
+                rule,
+                severity,
+                document: self.document.clone(),
+                location,
+                message,
+            });
+        }
+    }
+
+    fn lint_specification(&mut self, specs: &Specification) {
+        for (ind, method) in specs.methods.iter().enumerate() {
+            let location = format!("#/methods/{ind}");
+
+            if method.description.is_none() && method.summary.is_empty() {
+                self.report(
+                    Rule::MissingDescription,
+                    location.clone(),
+                    format!("Method {} has no summary or description", method.name),
+                );
+            }
+
+            for (ind, param) in method.params.iter().enumerate() {
+                let location = format!("{location}/params/{ind}");
+                if param.description.is_none() && param.summary.is_none() {
+                    self.report(
+                        Rule::MissingDescription,
+                        location.clone(),
+                        format!(
+                            "Param {} of {} has no summary or description",
+                            param.name, method.name
+                        ),
+                    );
+                }
+                self.lint_schema(&param.schema, &format!("{location}/schema"));
+            }
+
+            if method.result.description.is_none() && method.result.summary.is_none() {
+                self.report(
+                    Rule::MissingDescription,
+                    format!("{location}/result"),
+                    format!("Result of {} has no summary or description", method.name),
+                );
+            }
+            self.lint_schema(&method.result.schema, &format!("{location}/result/schema"));
+        }
+
+        for (name, schema) in specs.components.schemas.iter() {
+            let location = format!("#/components/schemas/{}", escape_pointer(name));
+
+            if !self.schema_name_regex.is_match(name) {
+                self.report(
+                    Rule::SchemaNaming,
+                    location.clone(),
+                    format!("Schema name {name} is not SCREAMING_SNAKE_CASE"),
+                );
+            }
+
+            // Aliases are documented by the schemas they point to
+            if !matches!(schema, Schema::Ref(_))
+                && schema.description().is_none()
+                && schema.title().is_none()
+            {
+                self.report(
+                    Rule::MissingDescription,
+                    location.clone(),
+                    format!("Schema {name} has no title or description"),
+                );
+            }
+
+            self.lint_schema(schema, &location);
+        }
+    }
+
+    /// Checks the objects defined in a schema, including nested ones.
+    fn lint_schema(&mut self, schema: &Schema, location: &str) {
+        match schema {
+            Schema::Ref(_) | Schema::Primitive(Primitive::Boolean(_)) => {}
+            Schema::OneOf(value) => self.lint_schemas(&value.one_of, &format!("{location}/oneOf")),
+            Schema::AllOf(value) => self.lint_schemas(&value.all_of, &format!("{location}/allOf")),
+            Schema::AnyOf(value) => self.lint_schemas(&value.any_of, &format!("{location}/anyOf")),
+            Schema::Not(value) => self.lint_schema(&value.not, &format!("{location}/not")),
+            Schema::Primitive(Primitive::Array(value)) => {
+                self.lint_schema(&value.items, &format!("{location}/items"))
+            }
+            Schema::Primitive(Primitive::Object(value)) => {
+                if !value.properties.is_empty() && value.required.is_none() {
+                    self.report(
+                        Rule::MissingRequired,
+                        location.to_owned(),
+                        String::from("Object has properties but no `required` list"),
+                    );
+                }
+
+                for (name, property) in value.properties.iter() {
+                    let location = format!("{location}/properties/{}", escape_pointer(name));
+
+                    if !self.property_name_regex.is_match(name) {
+                        self.report(
+                            Rule::PropertyNaming,
+                            location.clone(),
+                            format!("Property name {name} is not snake_case"),
+                        );
+                    }
+
+                    if property.description().is_none()
+                        && property.title().is_none()
+                        && property.summary().is_none()
+                    {
+                        self.report(
+                            Rule::MissingDescription,
+                            location.clone(),
+                            format!("Property {name} has no title or description"),
+                        );
+                    }
+
+                    self.lint_schema(property, &location);
+                }
+
+                if let Some(AdditionalProperties::Schema(schema)) = &value.additional_properties {
+                    self.lint_schema(schema, &format!("{location}/additionalProperties"));
+                }
+            }
+            Schema::Primitive(
+                Primitive::Integer(_)
+                | Primitive::Number(_)
+                | Primitive::Null(_)
+                | Primitive::String(_),
+            ) => {}
+        }
+    }
+
+    fn lint_schemas(&mut self, schemas: &[Schema], location: &str) {
+        for (ind, schema) in schemas.iter().enumerate() {
+            self.lint_schema(schema, &format!("{location}/{ind}"));
+        }
+    }
+}
+
+/// Escapes a name for use as a JSON pointer segment.
+fn escape_pointer(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
@@ -3,3 +3,12 @@ pub use generate::Generate;
 
 mod print;
 pub use print::Print;
+
+mod export_schema;
+pub use export_schema::ExportSchema;
+
+mod docs;
+pub use docs::Docs;
+
+mod lint;
+pub use lint::Lint;